@@ -3,7 +3,10 @@ use yew_router::prelude::*;
 
 use crate::components::header::Header;
 use crate::modules::home;
+use crate::modules::search;
 use crate::modules::story;
+use crate::modules::user;
+use crate::utils::initial_data::remove_server_rendered;
 
 use super::router::AppRoute;
 
@@ -27,6 +30,13 @@ impl Component for App {
         false
     }
 
+    fn rendered(&mut self, first_render: bool) {
+        // the page rendered by the server is shown until the app takes over
+        if first_render {
+            remove_server_rendered();
+        }
+    }
+
     fn view(&self) -> Html {
         html! {
           <>
@@ -35,8 +45,16 @@ impl Component for App {
                 <Router<AppRoute, ()>
                 render = Router::render(|switch: AppRoute| {
                         match switch {
-                            AppRoute::Home => html!{<home::Index />},
-                            AppRoute::Story(id) => html!{<story::Index id=id />}
+                            AppRoute::Home => html!{<home::Index feed="new" />},
+                            AppRoute::Top => html!{<home::Index feed="top" />},
+                            AppRoute::Best => html!{<home::Index feed="best" />},
+                            AppRoute::New => html!{<home::Index feed="new" />},
+                            AppRoute::Ask => html!{<home::Index feed="ask" />},
+                            AppRoute::Show => html!{<home::Index feed="show" />},
+                            AppRoute::Job => html!{<home::Index feed="job" />},
+                            AppRoute::Story(id) => html!{<story::Index id=id />},
+                            AppRoute::User(id) => html!{<user::Index id=id />},
+                            AppRoute::Search(query) => html!{<search::Index query=query />}
                         }
                     })
                 />
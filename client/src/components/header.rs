@@ -1,18 +1,69 @@
+use common::views;
 use yew::prelude::*;
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
+use yew_router::route::Route;
 
-pub struct Header {}
+use crate::router::{router_link, AppRoute};
 
-pub enum Msg {}
+pub struct Header {
+    link: ComponentLink<Self>,
+    router: RouteAgentDispatcher<()>,
+    query: String,
+}
+
+pub enum Msg {
+    UpdateQuery(String),
+    Search,
+}
+
+impl Header {
+    fn render_search(&self) -> Html {
+        html! {
+            <form id="search" onsubmit=self.link.callback(|e: FocusEvent| {
+                e.prevent_default();
+                Msg::Search
+            })>
+                <input
+                    type="search"
+                    placeholder="Search stories and comments"
+                    value=self.query.clone()
+                    oninput=self.link.callback(|e: InputData| Msg::UpdateQuery(e.value))
+                />
+            </form>
+        }
+    }
+}
 
 impl Component for Header {
     type Message = Msg;
     type Properties = ();
 
-    fn create(_: Self::Properties, _: ComponentLink<Self>) -> Self {
-        Self {}
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            router: RouteAgentDispatcher::new(),
+            query: String::new(),
+        }
     }
 
-    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::UpdateQuery(query) => {
+                self.query = query;
+            }
+            Msg::Search => {
+                let query = self.query.trim();
+
+                if query.is_empty() {
+                    return false;
+                }
+
+                let route = Route::from(AppRoute::search(query));
+
+                self.router.send(RouteRequest::ChangeRoute(route));
+            }
+        }
+
         true
     }
 
@@ -23,27 +74,9 @@ impl Component for Header {
     fn view(&self) -> Html {
         html! {
           <header id="app-header">
-            <div id="header-wrapper">
-                <div id="navigation">
-                    <button style="display: none;">
-                        <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 16 16">
-                            <path fill-rule="evenodd" d="M2.5 12a.5.5 0 0 1 .5-.5h10a.5.5 0 0 1 0 1H3a.5.5 0 0 1-.5-.5zm0-4a.5.5 0 0 1 .5-.5h10a.5.5 0 0 1 0 1H3a.5.5 0 0 1-.5-.5zm0-4a.5.5 0 0 1 .5-.5h10a.5.5 0 0 1 0 1H3a.5.5 0 0 1-.5-.5z"/>
-                        </svg>
-                    </button>
-                </div>
-                <div id="branding">
-                    <h1>{"FluxCap"}</h1>
-                    <small>{"Powered by HackerNews and Firebase"}</small>
-                </div>
-                <div id="user">
-                    <button style="display: none;">
-                        <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 16 16">
-                            <path d="M11 6a3 3 0 1 1-6 0 3 3 0 0 1 6 0z"/>
-                            <path fill-rule="evenodd" d="M0 8a8 8 0 1 1 16 0A8 8 0 0 1 0 8zm8-7a7 7 0 0 0-5.468 11.37C3.242 11.226 4.805 10 8 10s4.757 1.225 5.468 2.37A7 7 0 0 0 8 1z"/>
-                        </svg>
-                    </button>
-                </div>
-            </div>
+            { views::header_branding() }
+            { self.render_search() }
+            { views::feed_tabs(router_link) }
           </header>
         }
     }
@@ -1,29 +1,74 @@
+use common::html::SanitizedHtml;
+use wasm_bindgen::JsCast;
 use yew::prelude::*;
 use yew::web_sys::Element;
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
+use yew_router::route::Route;
 
+/// Renders HTML as it is, it must be sanitized with `utils::html::sanitize`
+/// first.
 #[derive(Debug, Clone, Eq, PartialEq, Properties)]
 pub struct RawHtmlProps {
-    pub inner_html: String,
+    pub inner_html: SanitizedHtml,
 }
 
 pub struct RawHtml {
     props: RawHtmlProps,
+    link: ComponentLink<Self>,
+    router: RouteAgentDispatcher<()>,
     node_ref: NodeRef,
 }
 
+pub enum Msg {
+    /// A link within the app was clicked, holds its path
+    FollowLink(Option<String>),
+}
+
+impl RawHtml {
+    /// Path of the link `event` clicked on, if it's a link within the app.
+    /// Clicks meant to open the link elsewhere are left to the browser.
+    fn internal_link(event: &MouseEvent) -> Option<String> {
+        if event.button() != 0
+            || event.ctrl_key()
+            || event.meta_key()
+            || event.shift_key()
+            || event.alt_key()
+        {
+            return None;
+        }
+
+        event
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .and_then(|element| element.closest("a[href]").ok().flatten())
+            .and_then(|anchor| anchor.get_attribute("href"))
+            .filter(|href| href.starts_with('/') && !href.starts_with("//"))
+    }
+}
+
 impl Component for RawHtml {
-    type Message = ();
+    type Message = Msg;
     type Properties = RawHtmlProps;
 
-    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         Self {
             props,
+            link,
+            router: RouteAgentDispatcher::new(),
             node_ref: NodeRef::default(),
         }
     }
 
-    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
-        unreachable!()
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FollowLink(Some(path)) => {
+                self.router
+                    .send(RouteRequest::ChangeRoute(Route::new_no_state(path)));
+            }
+            Msg::FollowLink(None) => {}
+        }
+
+        false
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
@@ -37,13 +82,25 @@ impl Component for RawHtml {
 
     fn rendered(&mut self, _first_render: bool) {
         let el = self.node_ref.cast::<Element>().unwrap();
-        el.set_inner_html(&self.props.inner_html);
+        el.set_inner_html(self.props.inner_html.as_str());
     }
 
     fn view(&self) -> Html {
+        // links to stories are followed by the router instead of loading
+        // the app again
+        let onclick = self.link.callback(|event: MouseEvent| {
+            let path = RawHtml::internal_link(&event);
+
+            if path.is_some() {
+                event.prevent_default();
+            }
+
+            Msg::FollowLink(path)
+        });
+
         // create the parent element and store a reference to it
         html! {
-            <div ref=self.node_ref.clone()/>
+            <div ref=self.node_ref.clone() onclick=onclick />
         }
     }
 }
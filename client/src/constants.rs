@@ -1,13 +1,27 @@
 pub mod api {
     #[cfg(debug_assertions)]
     pub mod v1 {
+        pub const JOBS: &str = "http://0.0.0.0:3000/api/v1/jobs";
+        pub const LIVE_STORIES: &str = "http://0.0.0.0:3000/api/v1/stories/live";
+        pub const POLLS: &str = "http://0.0.0.0:3000/api/v1/polls";
         pub const PREVIEWS: &str = "http://0.0.0.0:3000/api/v1/previews";
+        pub const PREVIEWS_BATCH: &str = "http://0.0.0.0:3000/api/v1/previews/batch";
+        pub const SEARCH: &str = "http://0.0.0.0:3000/api/v1/search";
         pub const STORIES: &str = "http://0.0.0.0:3000/api/v1/stories";
+        pub const STORIES_WS: &str = "ws://0.0.0.0:3000/api/v1/stories";
+        pub const USERS: &str = "http://0.0.0.0:3000/api/v1/users";
     }
 
     #[cfg(not(debug_assertions))]
     pub mod v1 {
+        pub const JOBS: &str = "https://fluxcap.herokuapp.com/api/v1/jobs";
+        pub const LIVE_STORIES: &str = "https://fluxcap.herokuapp.com/api/v1/stories/live";
+        pub const POLLS: &str = "https://fluxcap.herokuapp.com/api/v1/polls";
         pub const PREVIEWS: &str = "https://fluxcap.herokuapp.com/api/v1/previews";
+        pub const PREVIEWS_BATCH: &str = "https://fluxcap.herokuapp.com/api/v1/previews/batch";
+        pub const SEARCH: &str = "https://fluxcap.herokuapp.com/api/v1/search";
         pub const STORIES: &str = "https://fluxcap.herokuapp.com/api/v1/stories";
+        pub const STORIES_WS: &str = "wss://fluxcap.herokuapp.com/api/v1/stories";
+        pub const USERS: &str = "https://fluxcap.herokuapp.com/api/v1/users";
     }
 }
@@ -0,0 +1,103 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use common::hacker_news;
+use common::html::ITEM_TEXT;
+use yew::prelude::*;
+
+use crate::components::raw_html::RawHtml;
+use crate::utils::html::sanitize;
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub job: hacker_news::Job,
+}
+
+/// Job postings have no score nor comments, only the posting itself and
+/// optionally a link to apply
+pub struct Job {
+    props: Props,
+}
+
+impl Job {
+    fn render_time(&self) -> Html {
+        let naive = NaiveDateTime::from_timestamp(self.props.job.time as i64, 0);
+        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
+        let time = datetime.format("%Y-%m-%d at %H:%M").to_string();
+
+        html! {
+            <span class="story-field">
+                <figure>
+                    <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" class="bi bi-clock" viewBox="0 0 16 16">
+                        <path d="M8 3.5a.5.5 0 0 0-1 0V9a.5.5 0 0 0 .252.434l3.5 2a.5.5 0 0 0 .496-.868L8 8.71V3.5z"/>
+                        <path d="M8 16A8 8 0 1 0 8 0a8 8 0 0 0 0 16zm7-8A7 7 0 1 1 1 8a7 7 0 0 1 14 0z"/>
+                    </svg>
+                </figure>
+                {time}
+            </span>
+        }
+    }
+
+    fn render_text(&self) -> Html {
+        if let Some(text) = self.props.job.text.clone() {
+            return html! {
+                <article class="job-text">
+                    <RawHtml inner_html=sanitize(&text, &ITEM_TEXT) />
+                </article>
+            };
+        }
+
+        Html::default()
+    }
+
+    fn render_link(&self) -> Html {
+        if let Some(url) = self.props.job.url.clone() {
+            return html! {
+                <span class="story-field action-button">
+                    <a href={url} class="read-more-button" target="_blank">
+                        {"View Posting"}
+                    </a>
+                </span>
+            };
+        }
+
+        Html::default()
+    }
+}
+
+impl Component for Job {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        Self { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <li class="story job">
+                <header>
+                    { self.render_time() }
+                </header>
+                <main>
+                    <h2 class="story-title">{self.props.job.title.clone()}</h2>
+                    { self.render_text() }
+                </main>
+                <footer>
+                    { self.render_link() }
+                </footer>
+            </li>
+        }
+    }
+}
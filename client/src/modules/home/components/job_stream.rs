@@ -0,0 +1,160 @@
+use anyhow::Error;
+use common::hacker_news;
+use common::Page;
+use yew::format::{Json, Nothing};
+use yew::prelude::*;
+use yew::services::fetch::{FetchOptions, FetchTask, Request, Response};
+use yew::services::FetchService;
+use yew::web_sys::RequestMode;
+
+use crate::constants::api;
+
+use super::Job;
+
+pub struct JobStream {
+    error_message: Option<String>,
+    fetch_task: Option<FetchTask>,
+    is_loading: bool,
+    is_loading_more_jobs: bool,
+    link: ComponentLink<Self>,
+    jobs: Option<Vec<hacker_news::Job>>,
+    next_cursor: Option<String>,
+}
+
+pub enum Msg {
+    FetchJobs,
+    FetchSucced(Page<hacker_news::Job>),
+    FetchFailed(String),
+}
+
+impl JobStream {
+    fn render_load_more_button(&self) -> Html {
+        let class_name = "action-button load-more-stories-button";
+
+        if self.next_cursor.is_none() {
+            return Html::default();
+        }
+
+        if self.is_loading_more_jobs {
+            return html! {
+                <button disabled=true class=class_name>
+                    {"Loading Jobs"}
+                </button>
+            };
+        }
+
+        html! {
+            <button class=class_name onclick=self.link.callback(|_| Msg::FetchJobs)>
+                {"Load more jobs"}
+            </button>
+        }
+    }
+}
+
+impl Component for JobStream {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            error_message: None,
+            fetch_task: None,
+            is_loading: true,
+            is_loading_more_jobs: false,
+            link,
+            jobs: None,
+            next_cursor: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FetchJobs => {
+                if self.jobs.is_some() {
+                    self.is_loading_more_jobs = true;
+                } else {
+                    self.is_loading = true;
+                }
+
+                self.error_message = None;
+
+                let url = match self.next_cursor.clone() {
+                    Some(cursor) => format!("{}?cursor={}", api::v1::JOBS, cursor),
+                    None => String::from(api::v1::JOBS),
+                };
+                let request = Request::get(url).body(Nothing).unwrap();
+                let callback = self.link.callback(
+                    |res: Response<Json<Result<Page<hacker_news::Job>, Error>>>| {
+                        let Json(data) = res.into_body();
+
+                        match data {
+                            Ok(page) => Msg::FetchSucced(page),
+                            Err(err) => Msg::FetchFailed(err.to_string()),
+                        }
+                    },
+                );
+
+                let mut options = FetchOptions::default();
+
+                options.mode = Some(RequestMode::Cors);
+
+                let task = FetchService::fetch_with_options(request, options, callback).unwrap();
+
+                self.fetch_task = Some(task);
+            }
+            Msg::FetchSucced(page) => {
+                self.jobs.get_or_insert_with(Vec::new).extend(page.items);
+                self.next_cursor = page.next_cursor;
+                self.is_loading = false;
+                self.is_loading_more_jobs = false;
+                self.error_message = None;
+            }
+            Msg::FetchFailed(error_message) => {
+                self.is_loading = false;
+                self.is_loading_more_jobs = false;
+                self.error_message = Some(error_message);
+            }
+        };
+
+        true
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.update(Msg::FetchJobs);
+        }
+    }
+
+    fn view(&self) -> Html {
+        if self.is_loading {
+            return html! {
+                <h1>{"Fetching the latest jobs"}</h1>
+            };
+        }
+
+        if let Some(error_message) = self.error_message.clone() {
+            return html! {
+                <h1>{"An error ocurred fetching jobs!"}<br />{error_message}</h1>
+            };
+        }
+
+        if let Some(jobs) = &self.jobs {
+            return html! {
+                <div id="stream-wrapper">
+                    <ul id="stream">
+                        { for jobs.iter().map(|job| html! { <Job job=job.clone() /> }) }
+                    </ul>
+                    { self.render_load_more_button() }
+                </div>
+            };
+        }
+
+        html! {
+            <h2>{"No jobs found at this time. Retry later today!"}</h2>
+        }
+    }
+}
@@ -1,5 +1,9 @@
+mod job;
+mod job_stream;
 mod story;
 mod stream;
 
+pub use job::Job;
+pub use job_stream::JobStream;
 pub use story::Story;
 pub use stream::Stream;
@@ -1,5 +1,11 @@
 use anyhow::Error;
 use common::hacker_news;
+use common::hacker_news::LiveEvent;
+use common::{LinkPreview, LinkPreviewBatch, Page};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{EventSource, MessageEvent};
 use yew::format::{Json, Nothing};
 use yew::prelude::*;
 use yew::services::fetch::{FetchOptions, FetchTask, Request, Response};
@@ -7,53 +13,135 @@ use yew::services::FetchService;
 use yew::web_sys::RequestMode;
 
 use crate::constants::api;
+use crate::utils::initial_data::take_stream;
 
 use super::Story;
 
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub feed: &'static str,
+    /// Subscribes to live updates, merging score and comment changes into
+    /// the listed stories and announcing new ones
+    #[prop_or(false)]
+    pub live: bool,
+}
+
 pub struct Stream {
+    props: Props,
     error_message: Option<String>,
     fetch_task: Option<FetchTask>,
     is_loading: bool,
     is_loading_more_stories: bool,
     link: ComponentLink<Self>,
     stories: Option<Vec<hacker_news::Story>>,
-    current_page: usize,
+    next_cursor: Option<String>,
+    /// Stories which arrived through live updates and are not listed yet
+    new_stories: Vec<hacker_news::Story>,
+    event_source: Option<EventSource>,
+    on_live_event: Option<Closure<dyn FnMut(MessageEvent)>>,
+    /// Previews of the listed stories by URL, fetched a page at a time
+    previews: HashMap<String, LinkPreview>,
+    pending_previews: HashSet<String>,
+    preview_fetch_tasks: Vec<FetchTask>,
 }
 
 pub enum Msg {
     FetchStories,
-    FetchSucced(Vec<hacker_news::Story>),
+    FetchSucced(Page<hacker_news::Story>),
     FetchFailed(String),
     FetchNextStoryPage,
-    FetchNextStoryPageSucced(Vec<hacker_news::Story>),
+    FetchNextStoryPageSucced(Page<hacker_news::Story>),
     FetchNextStoryPageFailed(String),
+    LiveEventReceived(LiveEvent),
+    ShowNewStories,
+    FetchPreviews(Vec<String>),
+    FetchPreviewsSucced(Vec<String>, HashMap<String, LinkPreview>),
+    FetchPreviewsFailed(Vec<String>),
 }
 
 impl Stream {
-    fn render_story(story: hacker_news::Story) -> Html {
-        let hacker_news::Story {
-            title,
-            by,
-            descendants: _,
-            id,
-            kids,
-            score,
-            time,
-            r#type: _,
-            url,
-        } = story;
-        let image_url: Option<String> = None;
+    fn subscribe(&mut self) {
+        let event_source = match EventSource::new(api::v1::LIVE_STORIES) {
+            Ok(event_source) => event_source,
+            Err(_) => return,
+        };
+        let link = self.link.clone();
+        let on_live_event = Closure::wrap(Box::new(move |event: MessageEvent| {
+            if let Some(data) = event.data().as_string() {
+                let Json(live_event): Json<Result<LiveEvent, Error>> = Json::from(Ok(data));
+
+                if let Ok(live_event) = live_event {
+                    link.send_message(Msg::LiveEventReceived(live_event));
+                }
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+
+        event_source.set_onmessage(Some(on_live_event.as_ref().unchecked_ref()));
+        self.event_source = Some(event_source);
+        self.on_live_event = Some(on_live_event);
+    }
+
+    fn unsubscribe(&mut self) {
+        if let Some(event_source) = self.event_source.take() {
+            event_source.close();
+        }
+
+        self.on_live_event = None;
+    }
+
+    fn is_listed(&self, id: u64) -> bool {
+        let listed = self
+            .stories
+            .as_ref()
+            .map(|stories| stories.iter().any(|story| story.id == id))
+            .unwrap_or(false);
+
+        listed || self.new_stories.iter().any(|story| story.id == id)
+    }
+
+    fn render_new_stories_banner(&self) -> Html {
+        if self.new_stories.is_empty() {
+            return Html::default();
+        }
+
+        let label = if self.new_stories.len() == 1 {
+            String::from("1 new story")
+        } else {
+            format!("{} new stories", self.new_stories.len())
+        };
+
+        html! {
+            <button class="new-stories-banner" onclick=self.link.callback(|_| Msg::ShowNewStories)>
+                {label}
+            </button>
+        }
+    }
+
+    fn story_urls(stories: &[hacker_news::Story]) -> Vec<String> {
+        stories
+            .iter()
+            .filter_map(|story| story.url.clone())
+            .collect()
+    }
+
+    fn render_story(&self, story: hacker_news::Story) -> Html {
+        let preview = story
+            .url
+            .as_ref()
+            .and_then(|url| self.previews.get(url).cloned());
+        let is_loading_preview = story
+            .url
+            .as_ref()
+            .map(|url| self.pending_previews.contains(url))
+            .unwrap_or(false);
+        let key = story.id.to_string();
 
         html! {
             <Story
-                id=id
-                by=by
-                title=title
-                image_url=image_url
-                score=score
-                url=url
-                time=time
-                kids=kids
+                key=key
+                story=story
+                preview=preview
+                is_loading_preview=is_loading_preview
             />
         }
     }
@@ -61,6 +149,10 @@ impl Stream {
     fn render_load_more_button(&self) -> Html {
         let class_name = "action-button load-more-stories-button";
 
+        if self.next_cursor.is_none() {
+            return Html::default();
+        }
+
         if self.is_loading_more_stories {
             return html! {
                 <button disabled=true class=class_name>
@@ -80,10 +172,11 @@ impl Stream {
         if let Some(stories) = &self.stories {
             return html! {
                 <div id="stream-wrapper">
+                    { self.render_new_stories_banner() }
                     <ul id="stream">
                         {
-                            for stories.into_iter().map(|story| {
-                                Stream::render_story(story.clone())
+                            for stories.iter().map(|story| {
+                                self.render_story(story.clone())
                             })
                         }
                     </ul>
@@ -100,17 +193,28 @@ impl Stream {
 
 impl Component for Stream {
     type Message = Msg;
-    type Properties = ();
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        // pages rendered by the server start from the stories these were
+        // rendered with
+        let page = take_stream(props.feed);
 
-    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
         Self {
+            props,
             error_message: None,
             fetch_task: None,
-            is_loading: true,
+            is_loading: page.is_none(),
             is_loading_more_stories: false,
             link,
-            stories: None,
-            current_page: 0,
+            next_cursor: page.as_ref().and_then(|page| page.next_cursor.clone()),
+            stories: page.map(|page| page.items),
+            new_stories: Vec::new(),
+            event_source: None,
+            on_live_event: None,
+            previews: HashMap::new(),
+            pending_previews: HashSet::new(),
+            preview_fetch_tasks: Vec::new(),
         }
     }
 
@@ -120,13 +224,16 @@ impl Component for Stream {
                 self.is_loading = true;
                 self.error_message = None;
 
-                let request = Request::get(api::v1::STORIES).body(Nothing).unwrap();
+                let request =
+                    Request::get(format!("{}?feed={}", api::v1::STORIES, self.props.feed))
+                        .body(Nothing)
+                        .unwrap();
                 let callback = self.link.callback(
-                    |res: Response<Json<Result<Vec<hacker_news::Story>, Error>>>| {
+                    |res: Response<Json<Result<Page<hacker_news::Story>, Error>>>| {
                         let Json(data) = res.into_body();
 
                         match data {
-                            Ok(stories) => Msg::FetchSucced(stories),
+                            Ok(page) => Msg::FetchSucced(page),
                             Err(err) => Msg::FetchFailed(err.to_string()),
                         }
                     },
@@ -144,36 +251,40 @@ impl Component for Stream {
                 self.is_loading = false;
                 self.error_message = Some(error_message);
             }
-            Msg::FetchSucced(stories) => {
-                if let Some(current_stories) = self.stories.clone() {
-                    let mut next_stories = current_stories.clone();
-
-                    stories
-                        .into_iter()
-                        .for_each(|story| next_stories.push(story));
-                    self.stories = Some(next_stories);
-                } else {
-                    self.stories = Some(stories);
-                }
+            Msg::FetchSucced(page) => {
+                let urls = Stream::story_urls(&page.items);
 
+                self.stories = Some(page.items);
+                self.next_cursor = page.next_cursor;
                 self.is_loading = false;
                 self.error_message = None;
-                self.current_page += 1;
+                self.update(Msg::FetchPreviews(urls));
             }
             Msg::FetchNextStoryPage => {
+                let cursor = match self.next_cursor.clone() {
+                    Some(cursor) => cursor,
+                    None => return false,
+                };
+
                 self.is_loading_more_stories = true;
                 self.error_message = None;
 
-                let page = self.current_page + 1;
-                let request = Request::get(format!("{}?page={}", api::v1::STORIES, page))
-                    .body(Nothing)
-                    .unwrap();
+                // the cursor points into the same list the first page was
+                // taken from, so stories arriving meanwhile don't shift it
+                let request = Request::get(format!(
+                    "{}?feed={}&cursor={}",
+                    api::v1::STORIES,
+                    self.props.feed,
+                    cursor
+                ))
+                .body(Nothing)
+                .unwrap();
                 let callback = self.link.callback(
-                    |res: Response<Json<Result<Vec<hacker_news::Story>, Error>>>| {
+                    |res: Response<Json<Result<Page<hacker_news::Story>, Error>>>| {
                         let Json(data) = res.into_body();
 
                         match data {
-                            Ok(stories) => Msg::FetchNextStoryPageSucced(stories),
+                            Ok(page) => Msg::FetchNextStoryPageSucced(page),
                             Err(err) => Msg::FetchNextStoryPageFailed(err.to_string()),
                         }
                     },
@@ -191,16 +302,18 @@ impl Component for Stream {
                 self.is_loading_more_stories = false;
                 self.error_message = Some(error_message);
             }
-            Msg::FetchNextStoryPageSucced(stories) => {
+            Msg::FetchNextStoryPageSucced(page) => {
                 if let Some(current_stories) = self.stories.clone() {
                     let mut next_stories = current_stories.clone();
+                    let urls = Stream::story_urls(&page.items);
 
-                    stories
+                    page.items
                         .into_iter()
                         .for_each(|story| next_stories.push(story));
                     self.stories = Some(next_stories);
+                    self.next_cursor = page.next_cursor;
                     self.error_message = None;
-                    self.current_page += 1;
+                    self.update(Msg::FetchPreviews(urls));
                 } else {
                     // TODO: Improve this error handling for code's sake
                     self.error_message = Some(String::from("Expected at least one story but instead there's none. Refresh the site please"));
@@ -208,21 +321,143 @@ impl Component for Stream {
 
                 self.is_loading_more_stories = false;
             }
+            Msg::LiveEventReceived(LiveEvent::NewStory { story }) => {
+                // only the `new` feed is sorted by arrival
+                if self.props.feed != "new" || self.is_listed(story.id) {
+                    return false;
+                }
+
+                self.new_stories.insert(0, story);
+            }
+            Msg::LiveEventReceived(LiveEvent::StoryUpdated {
+                id,
+                score,
+                descendants,
+            }) => {
+                let story = self
+                    .stories
+                    .as_mut()
+                    .and_then(|stories| stories.iter_mut().find(|story| story.id == id));
+
+                match story {
+                    Some(story) => {
+                        story.score = score;
+                        story.descendants = descendants;
+                    }
+                    None => return false,
+                }
+            }
+            Msg::ShowNewStories => {
+                let mut stories: Vec<hacker_news::Story> = self.new_stories.drain(..).collect();
+                let urls = Stream::story_urls(&stories);
+
+                stories.extend(self.stories.take().unwrap_or_default());
+                self.stories = Some(stories);
+                self.update(Msg::FetchPreviews(urls));
+            }
+            Msg::FetchPreviews(urls) => {
+                let urls: Vec<String> = urls
+                    .into_iter()
+                    .filter(|url| {
+                        !self.previews.contains_key(url) && !self.pending_previews.contains(url)
+                    })
+                    .collect();
+
+                if urls.is_empty() {
+                    return false;
+                }
+
+                // a single request for the whole page instead of one per
+                // story
+                let batch = LinkPreviewBatch { urls: urls.clone() };
+                let request = Request::post(api::v1::PREVIEWS_BATCH)
+                    .header("Content-Type", "application/json")
+                    .body(Json(&batch))
+                    .unwrap();
+
+                self.pending_previews.extend(urls.iter().cloned());
+
+                let callback = self.link.callback(
+                    move |res: Response<Json<Result<HashMap<String, LinkPreview>, Error>>>| {
+                        let Json(data) = res.into_body();
+
+                        match data {
+                            Ok(previews) => Msg::FetchPreviewsSucced(urls.clone(), previews),
+                            Err(_) => Msg::FetchPreviewsFailed(urls.clone()),
+                        }
+                    },
+                );
+
+                let mut options = FetchOptions::default();
+
+                options.mode = Some(RequestMode::Cors);
+
+                let task = FetchService::fetch_with_options(request, options, callback).unwrap();
+
+                self.preview_fetch_tasks.push(task);
+            }
+            Msg::FetchPreviewsSucced(urls, previews) => {
+                for url in urls.iter() {
+                    self.pending_previews.remove(url);
+                }
+
+                self.previews.extend(previews);
+            }
+            Msg::FetchPreviewsFailed(urls) => {
+                // stories are listed without a description
+                for url in urls.iter() {
+                    self.pending_previews.remove(url);
+                }
+            }
         };
 
         true
     }
 
-    fn change(&mut self, _: Self::Properties) -> ShouldRender {
-        false
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props == props {
+            return false;
+        }
+
+        // switching between feed tabs reuses this component, previously
+        // fetched stories belong to another feed so the stream starts over
+        self.props = props;
+        self.stories = None;
+        self.next_cursor = None;
+        self.new_stories.clear();
+        self.pending_previews.clear();
+        self.preview_fetch_tasks.clear();
+
+        if self.props.live && self.event_source.is_none() {
+            self.subscribe();
+        } else if !self.props.live {
+            self.unsubscribe();
+        }
+
+        self.update(Msg::FetchStories)
     }
 
     fn rendered(&mut self, first_render: bool) {
         if first_render {
-            self.update(Msg::FetchStories);
+            match self.stories.as_ref() {
+                Some(stories) => self
+                    .link
+                    .send_message(Msg::FetchPreviews(Stream::story_urls(stories))),
+                None => {
+                    self.update(Msg::FetchStories);
+                }
+            }
+
+            if self.props.live {
+                self.subscribe();
+            }
         }
     }
 
+    fn destroy(&mut self) {
+        self.unsubscribe();
+    }
+
     fn view(&self) -> Html {
         if self.is_loading {
             return html! {
@@ -2,31 +2,51 @@ mod components;
 
 use yew::prelude::*;
 
-use self::components::Stream;
+use self::components::{JobStream, Stream};
 
-pub struct Index {}
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub feed: &'static str,
+}
+
+pub struct Index {
+    props: Props,
+}
 
 pub enum Msg {}
 
 impl Component for Index {
     type Message = Msg;
-    type Properties = ();
+    type Properties = Props;
 
-    fn create(_: Self::Properties, _: ComponentLink<Self>) -> Self {
-        Index {}
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        Index { props }
     }
 
     fn update(&mut self, _msg: Self::Message) -> ShouldRender {
         true
     }
 
-    fn change(&mut self, _: Self::Properties) -> ShouldRender {
-        false
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
     }
 
     fn view(&self) -> Html {
+        // job postings are rendered with their own cards, without score nor
+        // comments
+        if self.props.feed == "job" {
+            return html! {
+              <JobStream />
+            };
+        }
+
         html! {
-          <Stream />
+          <Stream feed=self.props.feed live=true />
         }
     }
 }
@@ -1,2 +1,4 @@
 pub mod home;
+pub mod search;
 pub mod story;
+pub mod user;
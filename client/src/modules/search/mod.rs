@@ -0,0 +1,309 @@
+use anyhow::Error;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use common::hacker_news::{SearchHit, Type};
+use common::html::SNIPPET;
+use common::Page;
+use yew::format::{Json, Nothing};
+use yew::prelude::*;
+use yew::services::fetch::{FetchOptions, FetchTask, Request, Response};
+use yew::services::FetchService;
+use yew::web_sys::RequestMode;
+use yew_router::components::RouterAnchor;
+
+use crate::components::raw_html::RawHtml;
+use crate::constants::api;
+use crate::router::AppRoute;
+use crate::utils::html::sanitize;
+
+/// Sort orders supported by the search endpoint along with their labels
+const SORTS: [(&str, &str); 2] = [("relevance", "Relevance"), ("date", "Date")];
+
+/// Item types which can be searched for along with their labels
+const TYPES: [(Option<&str>, &str); 3] = [
+    (None, "All"),
+    (Some("story"), "Stories"),
+    (Some("comment"), "Comments"),
+];
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    /// URI encoded search query, as found in `AppRoute::Search`
+    pub query: String,
+}
+
+pub struct Index {
+    props: Props,
+    link: ComponentLink<Self>,
+    is_loading: bool,
+    is_loading_more_hits: bool,
+    hits: Option<Vec<SearchHit>>,
+    next_cursor: Option<String>,
+    sort: &'static str,
+    r#type: Option<&'static str>,
+    fetch_task: Option<FetchTask>,
+    error_message: Option<String>,
+}
+
+pub enum Msg {
+    FetchHits(Option<String>),
+    FetchSucced(Page<SearchHit>),
+    FetchFailed(String),
+    SetSort(&'static str),
+    SetType(Option<&'static str>),
+}
+
+impl Index {
+    fn format_time(time: u64) -> String {
+        let naive = NaiveDateTime::from_timestamp(time as i64, 0);
+        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
+
+        datetime.format("%Y-%m-%d at %H:%M").to_string()
+    }
+
+    fn decoded_query(&self) -> String {
+        js_sys::decode_uri_component(&self.props.query)
+            .map(String::from)
+            .unwrap_or_else(|_| self.props.query.clone())
+    }
+
+    fn search_url(&self, cursor: Option<String>) -> String {
+        let mut url = format!(
+            "{}?q={}&sort={}",
+            api::v1::SEARCH,
+            self.props.query,
+            self.sort
+        );
+
+        if let Some(r#type) = self.r#type {
+            url.push_str(&format!("&type={}", r#type));
+        }
+
+        if let Some(cursor) = cursor {
+            url.push_str(&format!("&cursor={}", cursor));
+        }
+
+        url
+    }
+
+    fn render_filters(&self) -> Html {
+        html! {
+            <nav id="search-filters">
+                {
+                    for SORTS.iter().map(|(sort, label)| {
+                        let sort = *sort;
+                        let class_name = if self.sort == sort { "search-filter active" } else { "search-filter" };
+
+                        html! {
+                            <button class=class_name onclick=self.link.callback(move |_| Msg::SetSort(sort))>
+                                {label}
+                            </button>
+                        }
+                    })
+                }
+                <span class="search-filters-separator" />
+                {
+                    for TYPES.iter().map(|(r#type, label)| {
+                        let r#type = *r#type;
+                        let class_name = if self.r#type == r#type { "search-filter active" } else { "search-filter" };
+
+                        html! {
+                            <button class=class_name onclick=self.link.callback(move |_| Msg::SetType(r#type))>
+                                {label}
+                            </button>
+                        }
+                    })
+                }
+            </nav>
+        }
+    }
+
+    fn render_hit(hit: &SearchHit) -> Html {
+        let title = match (&hit.r#type, hit.title.clone()) {
+            (Type::Story, Some(title)) | (Type::Poll, Some(title)) => html! {
+                <RouterAnchor<AppRoute> route=AppRoute::Story(hit.id) classes="search-hit-title">
+                    {title}
+                </RouterAnchor<AppRoute>>
+            },
+            (_, Some(title)) => html! {
+                <span class="search-hit-title">{title}</span>
+            },
+            (_, None) => Html::default(),
+        };
+
+        html! {
+            <li class="search-hit">
+                <small>
+                    {
+                        if let Some(author) = hit.by.clone() {
+                            html! {
+                                <RouterAnchor<AppRoute> route=AppRoute::User(author.clone()) classes="author-link">
+                                    {author}
+                                </RouterAnchor<AppRoute>>
+                            }
+                        } else {
+                            Html::default()
+                        }
+                    }
+                    {format!(" {}", Index::format_time(hit.time))}
+                </small>
+                {title}
+                <RawHtml inner_html=sanitize(&hit.snippet, &SNIPPET) />
+            </li>
+        }
+    }
+
+    fn render_load_more_button(&self) -> Html {
+        let class_name = "action-button load-more-stories-button";
+
+        if self.next_cursor.is_none() {
+            return Html::default();
+        }
+
+        if self.is_loading_more_hits {
+            return html! {
+                <button disabled=true class=class_name>
+                    {"Loading Results"}
+                </button>
+            };
+        }
+
+        let cursor = self.next_cursor.clone();
+
+        html! {
+            <button class=class_name onclick=self.link.callback(move |_| Msg::FetchHits(cursor.clone()))>
+                {"Load more results"}
+            </button>
+        }
+    }
+
+    fn render_hits(&self) -> Html {
+        if self.is_loading {
+            return html! {
+                <h2>{"Searching"}</h2>
+            };
+        }
+
+        if let Some(error_message) = self.error_message.clone() {
+            return html! {
+                <h2>{"An error ocurred searching!"}<br />{error_message}</h2>
+            };
+        }
+
+        match &self.hits {
+            Some(hits) if !hits.is_empty() => html! {
+                <>
+                    <ul id="search-hits">
+                        { for hits.iter().map(Index::render_hit) }
+                    </ul>
+                    { self.render_load_more_button() }
+                </>
+            },
+            _ => html! {
+                <h2>{"Nothing matches your search"}</h2>
+            },
+        }
+    }
+}
+
+impl Component for Index {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Index {
+            props,
+            link,
+            is_loading: false,
+            is_loading_more_hits: false,
+            hits: None,
+            next_cursor: None,
+            sort: "relevance",
+            r#type: None,
+            fetch_task: None,
+            error_message: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FetchHits(cursor) => {
+                if cursor.is_some() {
+                    self.is_loading_more_hits = true;
+                } else {
+                    self.is_loading = true;
+                    self.hits = None;
+                }
+
+                self.error_message = None;
+
+                let request = Request::get(self.search_url(cursor)).body(Nothing).unwrap();
+                let callback =
+                    self.link
+                        .callback(|res: Response<Json<Result<Page<SearchHit>, Error>>>| {
+                            let Json(data) = res.into_body();
+
+                            match data {
+                                Ok(page) => Msg::FetchSucced(page),
+                                Err(err) => Msg::FetchFailed(err.to_string()),
+                            }
+                        });
+
+                let mut options = FetchOptions::default();
+
+                options.mode = Some(RequestMode::Cors);
+
+                let task = FetchService::fetch_with_options(request, options, callback).unwrap();
+
+                self.fetch_task = Some(task);
+            }
+            Msg::FetchSucced(page) => {
+                self.hits.get_or_insert_with(Vec::new).extend(page.items);
+                self.next_cursor = page.next_cursor;
+                self.is_loading = false;
+                self.is_loading_more_hits = false;
+            }
+            Msg::FetchFailed(error_message) => {
+                self.error_message = Some(error_message);
+                self.is_loading = false;
+                self.is_loading_more_hits = false;
+            }
+            Msg::SetSort(sort) => {
+                self.sort = sort;
+                return self.update(Msg::FetchHits(None));
+            }
+            Msg::SetType(r#type) => {
+                self.r#type = r#type;
+                return self.update(Msg::FetchHits(None));
+            }
+        }
+
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props == props {
+            return false;
+        }
+
+        self.props = props;
+        self.update(Msg::FetchHits(None))
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.update(Msg::FetchHits(None));
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <section id="search-page">
+                <header id="search-header">
+                    <h2>{format!("Results for \"{}\"", self.decoded_query())}</h2>
+                    { self.render_filters() }
+                </header>
+                { self.render_hits() }
+            </section>
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use anyhow::Error;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use common::hacker_news;
+use common::hacker_news::{CommentNode, ThreadEvent};
+use common::html::ITEM_TEXT;
+use std::collections::HashSet;
 use yew::format::{Json, Nothing};
 use yew::prelude::*;
 use yew::services::fetch::{FetchOptions, FetchTask, Request, Response};
+use yew::services::websocket::{WebSocketService, WebSocketTask};
 use yew::services::FetchService;
 use yew::web_sys::RequestMode;
 use yew_router::components::RouterAnchor;
@@ -11,6 +15,7 @@ use yew_router::components::RouterAnchor;
 use crate::components::raw_html::RawHtml;
 use crate::constants::api;
 use crate::router::AppRoute;
+use crate::utils::html::sanitize;
 use crate::utils::url::make_link_preview_url;
 
 #[derive(Debug, Clone, PartialEq, Properties)]
@@ -22,15 +27,22 @@ pub struct Comments {
     props: Props,
     link: ComponentLink<Self>,
     is_loading: bool,
-    items: Option<Vec<common::hacker_news::Comment>>,
+    items: Option<Vec<CommentNode>>,
+    collapsed: HashSet<u64>,
+    /// Comments which arrived while the thread was open
+    new_comments: HashSet<u64>,
     fetch_task: Option<FetchTask>,
+    websocket_task: Option<WebSocketTask>,
     error_message: Option<String>,
 }
 
 pub enum Msg {
     FetchStory,
-    FetchSucced(Vec<common::hacker_news::Comment>),
+    FetchSucced(Vec<CommentNode>),
     FetchFailed(String),
+    ToggleCollapse(u64),
+    Watch,
+    ThreadEventReceived(ThreadEvent),
 }
 
 impl Comments {
@@ -42,7 +54,9 @@ impl Comments {
                 <path d="M8 8a3 3 0 1 0 0-6 3 3 0 0 0 0 6zm2-3a2 2 0 1 1-4 0 2 2 0 0 1 4 0zm4 8c0 1-1 1-1 1H3s-1 0-1-1 1-4 6-4 6 3 6 4zm-1-.004c-.001-.246-.154-.986-.832-1.664C11.516 10.68 10.289 10 8 10c-2.29 0-3.516.68-4.168 1.332-.678.678-.83 1.418-.832 1.664h10z"/>
               </svg>
             </figure>
-            {author}
+            <RouterAnchor<AppRoute> route=AppRoute::User(author.clone()) classes="author-link">
+                {author}
+            </RouterAnchor<AppRoute>>
           </span>
         }
     }
@@ -66,48 +80,177 @@ impl Comments {
         }
     }
 
-    fn render_comments(&self) -> Html {
-        if let Some(comments) = self.items.clone() {
-            return html! {
-                <ul id="comments">
+    fn render_toggle(&self, node: &CommentNode) -> Html {
+        if node.replies.is_empty() {
+            return Html::default();
+        }
+
+        let id = node.comment.id;
+        let label = if self.collapsed.contains(&id) {
+            format!("[+] {} replies", Comments::count_replies(node))
+        } else {
+            String::from("[-]")
+        };
+
+        html! {
+            <button class="comment-toggle" onclick=self.link.callback(move |_| Msg::ToggleCollapse(id))>
+                {label}
+            </button>
+        }
+    }
+
+    fn render_replies(&self, node: &CommentNode) -> Html {
+        if node.replies.is_empty() || self.collapsed.contains(&node.comment.id) {
+            return Html::default();
+        }
+
+        html! {
+            <ul class="comment-replies">
+                { for node.replies.iter().map(|reply| self.render_node(reply)) }
+            </ul>
+        }
+    }
+
+    fn render_node(&self, node: &CommentNode) -> Html {
+        let comment = node.comment.clone();
+        let class_name = if self.new_comments.contains(&comment.id) {
+            "comment comment-new"
+        } else {
+            "comment"
+        };
+
+        html! {
+            <li class=class_name id=format!("#{}", comment.id)>
+                <header class="comment-header">
+                    {
+                        if let Some(author) = comment.by {
+                            self.render_author(author)
+                        } else {
+                            Html::default()
+                        }
+                    }
+                    <a class="comment-permalink" href=format!("#{}", comment.id)>
+                        { self.render_time(comment.time) }
+                    </a>
+                    { self.render_toggle(node) }
+                </header>
                 {
-                    for comments.into_iter().map(|comment| {
+                    if comment.text.is_some() && !self.collapsed.contains(&comment.id) {
                         html! {
-                            <li class="comment" id=format!("#{}", comment.id)>
-                                <a href=format!("#{}", comment.id)>
-                                    <header class="comment-header">
-                                        {
-                                            if let Some(author) = comment.by {
-                                                self.render_author(author)
-                                            } else {
-                                                Html::default()
-                                            }
-                                        }
-                                        { self.render_time(comment.time) }
-                                    </header>
-                                </a>
-                                {
-                                    if comment.text.is_some() {
-                                        html! {
-                                            <main class="comment-body">
-                                                <RawHtml inner_html=comment.text.clone().unwrap() />
-                                            </main>
-                                        }
-                                    } else {
-                                        Html::default()
-                                    }
-                                }
-
-                            </li>
+                            <main class="comment-body">
+                                <RawHtml inner_html=sanitize(comment.text.as_deref().unwrap_or_default(), &ITEM_TEXT) />
+                            </main>
                         }
-                    })
+                    } else {
+                        Html::default()
+                    }
                 }
+                { self.render_replies(node) }
+            </li>
+        }
+    }
+
+    fn render_comments(&self) -> Html {
+        if let Some(nodes) = &self.items {
+            return html! {
+                <ul id="comments">
+                    { for nodes.iter().map(|node| self.render_node(node)) }
                 </ul>
             };
         }
 
         Html::default()
     }
+
+    fn count_replies(node: &CommentNode) -> usize {
+        node.replies
+            .iter()
+            .map(|reply| 1 + Comments::count_replies(reply))
+            .sum()
+    }
+
+    fn find_node(nodes: &mut [CommentNode], id: u64) -> Option<&mut CommentNode> {
+        for node in nodes.iter_mut() {
+            if node.comment.id == id {
+                return Some(node);
+            }
+
+            if let Some(found) = Comments::find_node(&mut node.replies, id) {
+                return Some(found);
+            }
+        }
+
+        None
+    }
+
+    /// Comments with replies are kept as a placeholder, so their replies
+    /// remain reachable.
+    fn remove_node(nodes: &mut Vec<CommentNode>, id: u64) -> bool {
+        if let Some(index) = nodes.iter().position(|node| node.comment.id == id) {
+            if nodes[index].replies.is_empty() {
+                nodes.remove(index);
+            } else {
+                nodes[index].comment.by = None;
+                nodes[index].comment.text = Some(String::from("[deleted]"));
+            }
+
+            return true;
+        }
+
+        nodes
+            .iter_mut()
+            .any(|node| Comments::remove_node(&mut node.replies, id))
+    }
+
+    /// Applies a change to the thread, returns `false` if the thread is left
+    /// as it was.
+    fn apply(&mut self, event: ThreadEvent) -> bool {
+        let story_id = self.props.id;
+        let nodes = match self.items.as_mut() {
+            Some(nodes) => nodes,
+            None => return false,
+        };
+
+        match event {
+            ThreadEvent::CommentAdded { comment, position } => {
+                let id = comment.id;
+
+                if Comments::find_node(nodes, id).is_some() {
+                    return false;
+                }
+
+                let siblings = match comment.parent {
+                    Some(parent) if parent == story_id => nodes,
+                    Some(parent) => match Comments::find_node(nodes, parent) {
+                        Some(parent) => &mut parent.replies,
+                        None => return false,
+                    },
+                    None => return false,
+                };
+
+                siblings.insert(
+                    position.min(siblings.len()),
+                    CommentNode {
+                        comment,
+                        replies: Vec::new(),
+                    },
+                );
+                self.new_comments.insert(id);
+
+                true
+            }
+            ThreadEvent::CommentEdited { comment } => {
+                match Comments::find_node(nodes, comment.id) {
+                    Some(node) => {
+                        node.comment = comment;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            ThreadEvent::CommentDeleted { id } => Comments::remove_node(nodes, id),
+        }
+    }
 }
 
 impl Component for Comments {
@@ -120,7 +263,10 @@ impl Component for Comments {
             link,
             is_loading: false,
             items: None,
+            collapsed: HashSet::new(),
+            new_comments: HashSet::new(),
             fetch_task: None,
+            websocket_task: None,
             error_message: None,
         }
     }
@@ -131,19 +277,20 @@ impl Component for Comments {
                 self.is_loading = true;
                 self.error_message = None;
 
-                let request = Request::get(format!("{}/{}/kids", api::v1::STORIES, self.props.id))
-                    .body(Nothing)
-                    .unwrap();
-                let callback = self.link.callback(
-                    |res: Response<Json<Result<Vec<common::hacker_news::Comment>, Error>>>| {
-                        let Json(data) = res.into_body();
+                let request =
+                    Request::get(format!("{}/{}/thread", api::v1::STORIES, self.props.id))
+                        .body(Nothing)
+                        .unwrap();
+                let callback =
+                    self.link
+                        .callback(|res: Response<Json<Result<Vec<CommentNode>, Error>>>| {
+                            let Json(data) = res.into_body();
 
-                        match data {
-                            Ok(comments) => Msg::FetchSucced(comments),
-                            Err(err) => Msg::FetchFailed(err.to_string()),
-                        }
-                    },
-                );
+                            match data {
+                                Ok(comments) => Msg::FetchSucced(comments),
+                                Err(err) => Msg::FetchFailed(err.to_string()),
+                            }
+                        });
 
                 let mut options = FetchOptions::default();
 
@@ -161,6 +308,29 @@ impl Component for Comments {
                 self.error_message = Some(error_message);
                 self.is_loading = false;
             }
+            Msg::ToggleCollapse(id) => {
+                if !self.collapsed.remove(&id) {
+                    self.collapsed.insert(id);
+                }
+            }
+            Msg::Watch => {
+                let url = format!("{}/{}/ws", api::v1::STORIES_WS, self.props.id);
+                let callback =
+                    self.link
+                        .batch_callback(|Json(data): Json<Result<ThreadEvent, Error>>| {
+                            data.ok().map(Msg::ThreadEventReceived)
+                        });
+
+                // the thread remains readable without updates if the
+                // connection can't be made
+                self.websocket_task =
+                    WebSocketService::connect_text(&url, callback, Callback::noop()).ok();
+
+                return false;
+            }
+            Msg::ThreadEventReceived(event) => {
+                return self.apply(event);
+            }
         }
 
         true
@@ -173,6 +343,7 @@ impl Component for Comments {
     fn rendered(&mut self, first_render: bool) {
         if first_render {
             self.update(Msg::FetchStory);
+            self.update(Msg::Watch);
         }
     }
 
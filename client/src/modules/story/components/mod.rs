@@ -1,5 +1,9 @@
 mod comments;
+mod poll;
+mod reader;
 mod story;
 
 pub use comments::Comments;
+pub use poll::PollChart;
+pub use reader::Reader;
 pub use story::Story;
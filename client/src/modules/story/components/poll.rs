@@ -0,0 +1,161 @@
+use anyhow::Error;
+use common::hacker_news::{Poll, PollOption};
+use common::html::ITEM_TEXT;
+use yew::format::{Json, Nothing};
+use yew::prelude::*;
+use yew::services::fetch::{FetchOptions, FetchTask, Request, Response};
+use yew::services::FetchService;
+use yew::web_sys::RequestMode;
+
+use crate::components::raw_html::RawHtml;
+use crate::constants::api;
+use crate::utils::html::sanitize;
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub id: u64,
+}
+
+pub struct PollChart {
+    props: Props,
+    link: ComponentLink<Self>,
+    is_loading: bool,
+    poll: Option<Poll>,
+    fetch_task: Option<FetchTask>,
+    error_message: Option<String>,
+}
+
+pub enum Msg {
+    FetchPoll,
+    FetchSucced(Poll),
+    FetchFailed(String),
+}
+
+impl PollChart {
+    fn render_option(option: &PollOption, total_votes: u32) -> Html {
+        let percentage = if total_votes > 0 {
+            option.score as f64 * 100.0 / total_votes as f64
+        } else {
+            0.0
+        };
+
+        html! {
+            <li class="poll-option">
+                <header class="poll-option-header">
+                    {
+                        if let Some(text) = option.text.clone() {
+                            html! { <RawHtml inner_html=sanitize(&text, &ITEM_TEXT) /> }
+                        } else {
+                            Html::default()
+                        }
+                    }
+                    <strong class="poll-option-score">
+                        {format!("{} ({:.1}%)", option.score, percentage)}
+                    </strong>
+                </header>
+                <div class="poll-option-bar">
+                    <span style=format!("width: {:.1}%;", percentage) />
+                </div>
+            </li>
+        }
+    }
+}
+
+impl Component for PollChart {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        PollChart {
+            props,
+            link,
+            is_loading: false,
+            poll: None,
+            fetch_task: None,
+            error_message: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FetchPoll => {
+                self.is_loading = true;
+                self.error_message = None;
+
+                let request = Request::get(format!("{}/{}", api::v1::POLLS, self.props.id))
+                    .body(Nothing)
+                    .unwrap();
+                let callback = self
+                    .link
+                    .callback(|res: Response<Json<Result<Poll, Error>>>| {
+                        let Json(data) = res.into_body();
+
+                        match data {
+                            Ok(poll) => Msg::FetchSucced(poll),
+                            Err(err) => Msg::FetchFailed(err.to_string()),
+                        }
+                    });
+
+                let mut options = FetchOptions::default();
+
+                options.mode = Some(RequestMode::Cors);
+
+                let task = FetchService::fetch_with_options(request, options, callback).unwrap();
+
+                self.fetch_task = Some(task);
+            }
+            Msg::FetchSucced(poll) => {
+                self.poll = Some(poll);
+                self.is_loading = false;
+            }
+            Msg::FetchFailed(error_message) => {
+                self.error_message = Some(error_message);
+                self.is_loading = false;
+            }
+        }
+
+        true
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.update(Msg::FetchPoll);
+        }
+    }
+
+    fn view(&self) -> Html {
+        if self.is_loading {
+            return html! {
+                <p>{"Fetching poll options"}</p>
+            };
+        }
+
+        if let Some(error_message) = self.error_message.clone() {
+            return html! {
+                <p>{"Failed to fetch poll options"}<br />{error_message}</p>
+            };
+        }
+
+        if let Some(poll) = &self.poll {
+            let total_votes: u32 = poll.options.iter().map(|option| option.score).sum();
+            let mut options = poll.options.clone();
+
+            options.sort_by(|a, b| b.score.cmp(&a.score));
+
+            return html! {
+                <section class="poll">
+                    <ol class="poll-options">
+                        { for options.iter().map(|option| PollChart::render_option(option, total_votes)) }
+                    </ol>
+                    <small class="poll-total">{format!("{} votes", total_votes)}</small>
+                </section>
+            };
+        }
+
+        Html::default()
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::Error;
+use common::html::ARTICLE;
+use common::Article;
+use yew::format::{Json, Nothing};
+use yew::prelude::*;
+use yew::services::fetch::{FetchOptions, FetchTask, Request, Response, StatusCode};
+use yew::services::FetchService;
+use yew::web_sys::RequestMode;
+
+use crate::components::raw_html::RawHtml;
+use crate::constants::api;
+use crate::utils::html::sanitize;
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub id: u64,
+    /// URL of the page, linked to when it has no article
+    pub url: String,
+}
+
+/// Article of the page a story links to, as extracted by the server.
+pub struct Reader {
+    props: Props,
+    link: ComponentLink<Self>,
+    is_loading: bool,
+    article: Option<Article>,
+    is_missing: bool,
+    fetch_task: Option<FetchTask>,
+    error_message: Option<String>,
+}
+
+pub enum Msg {
+    FetchArticle,
+    FetchSucced(Article),
+    FetchMissing,
+    FetchFailed(String),
+}
+
+impl Component for Reader {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Reader {
+            props,
+            link,
+            is_loading: false,
+            article: None,
+            is_missing: false,
+            fetch_task: None,
+            error_message: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FetchArticle => {
+                self.is_loading = true;
+                self.error_message = None;
+
+                let request =
+                    Request::get(format!("{}/{}/article", api::v1::STORIES, self.props.id))
+                        .body(Nothing)
+                        .unwrap();
+                let callback = self
+                    .link
+                    .callback(|res: Response<Json<Result<Article, Error>>>| {
+                        // pages without an article are answered with a 404
+                        if res.status() == StatusCode::NOT_FOUND {
+                            return Msg::FetchMissing;
+                        }
+
+                        let Json(data) = res.into_body();
+
+                        match data {
+                            Ok(article) => Msg::FetchSucced(article),
+                            Err(err) => Msg::FetchFailed(err.to_string()),
+                        }
+                    });
+
+                let mut options = FetchOptions::default();
+
+                options.mode = Some(RequestMode::Cors);
+
+                let task = FetchService::fetch_with_options(request, options, callback).unwrap();
+
+                self.fetch_task = Some(task);
+            }
+            Msg::FetchSucced(article) => {
+                self.article = Some(article);
+                self.is_loading = false;
+            }
+            Msg::FetchMissing => {
+                self.is_missing = true;
+                self.is_loading = false;
+            }
+            Msg::FetchFailed(error_message) => {
+                self.error_message = Some(error_message);
+                self.is_loading = false;
+            }
+        }
+
+        true
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.update(Msg::FetchArticle);
+        }
+    }
+
+    fn view(&self) -> Html {
+        if self.is_loading {
+            return html! {
+                <p>{"Fetching article"}</p>
+            };
+        }
+
+        if self.is_missing {
+            return html! {
+                <p class="reader-missing">
+                    {"No article was found in this page, "}
+                    <a href={self.props.url.clone()} target="_blank" rel="noopener">{"read it on the site"}</a>
+                </p>
+            };
+        }
+
+        if let Some(error_message) = self.error_message.clone() {
+            return html! {
+                <p>{"Failed to fetch the article"}<br />{error_message}</p>
+            };
+        }
+
+        if let Some(article) = &self.article {
+            return html! {
+                <article class="reader">
+                    {
+                        if let Some(title) = article.title.clone() {
+                            html! { <h1 class="reader-title">{title}</h1> }
+                        } else {
+                            Html::default()
+                        }
+                    }
+                    {
+                        if let Some(byline) = article.byline.clone() {
+                            html! { <small class="reader-byline">{byline}</small> }
+                        } else {
+                            Html::default()
+                        }
+                    }
+                    <div class="reader-content">
+                        <RawHtml inner_html=sanitize(&article.content, &ARTICLE) />
+                    </div>
+                </article>
+            };
+        }
+
+        Html::default()
+    }
+}
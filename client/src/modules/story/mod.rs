@@ -1,30 +1,40 @@
 mod components;
 
 use anyhow::Error;
+use common::hacker_news::Type;
+use common::views;
 use yew::format::{Json, Nothing};
 use yew::prelude::*;
 use yew::services::fetch::{FetchOptions, FetchTask, Request, Response};
 use yew::services::FetchService;
 use yew::web_sys::RequestMode;
-use yew_router::components::RouterAnchor;
 
 use crate::constants::api;
-use crate::router::AppRoute;
+use crate::router::router_link;
+use crate::utils::initial_data::take_story;
 use crate::utils::url::make_link_preview_url;
 
-use self::components::Comments;
+use self::components::{Comments, PollChart, Reader};
 
 #[derive(Debug, Clone, PartialEq, Properties)]
 pub struct Props {
     pub id: u64,
 }
 
+/// Stories linking to a page can be read without leaving them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tab {
+    Comments,
+    Reader,
+}
+
 pub struct Index {
     props: Props,
     link: ComponentLink<Self>,
     is_loading: bool,
     story: Option<common::hacker_news::Story>,
     link_preview: Option<common::LinkPreview>,
+    tab: Tab,
     fetch_task: Option<FetchTask>,
     error_message: Option<String>,
 }
@@ -35,21 +45,42 @@ pub enum Msg {
     FetchFailed(String),
     FetchLinkPreview,
     FetchLinkPreviewSuccess(common::LinkPreview),
+    SelectTab(Tab),
 }
 
 impl Index {
-    fn render_story_image(&self) -> Html {
-        if let Some(link_preview) = self.link_preview.clone() {
-            if let Some(image_url) = link_preview.image_url {
-                return html! {
-                    <figure class="illustration">
-                        <img src={image_url} alt="Story image" />
-                    </figure>
-                };
+    fn render_tabs(&self, story: &common::hacker_news::Story) -> Html {
+        if story.url.is_none() {
+            return Html::default();
+        }
+
+        let tab = |tab: Tab, label: &str| {
+            let class = if self.tab == tab {
+                "story-tab active"
+            } else {
+                "story-tab"
+            };
+
+            html! {
+                <button class=class onclick=self.link.callback(move |_| Msg::SelectTab(tab))>
+                    {label}
+                </button>
             }
+        };
+
+        html! {
+            <nav class="story-tabs">
+                {tab(Tab::Comments, "Comments")}
+                {tab(Tab::Reader, "Reader")}
+            </nav>
         }
+    }
 
-        Html::default()
+    fn render_tab(&self, story: &common::hacker_news::Story) -> Html {
+        match (self.tab, story.url.clone()) {
+            (Tab::Reader, Some(url)) => html! { <Reader id=story.id url=url /> },
+            _ => html! { <Comments id=story.id /> },
+        }
     }
 }
 
@@ -58,12 +89,20 @@ impl Component for Index {
     type Properties = Props;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        // pages rendered by the server start from the story these were
+        // rendered with
+        let (story, link_preview) = match take_story(props.id) {
+            Some((story, link_preview)) => (Some(story), link_preview),
+            None => (None, None),
+        };
+
         Index {
             props,
             link,
             is_loading: false,
-            story: None,
-            link_preview: None,
+            story,
+            link_preview,
+            tab: Tab::Comments,
             fetch_task: None,
             error_message: None,
         }
@@ -140,6 +179,9 @@ impl Component for Index {
                 self.link_preview = Some(link_preview);
                 self.is_loading = false;
             }
+            Msg::SelectTab(tab) => {
+                self.tab = tab;
+            }
         }
 
         true
@@ -151,7 +193,14 @@ impl Component for Index {
 
     fn rendered(&mut self, first_render: bool) {
         if first_render {
-            self.update(Msg::FetchStory);
+            match (self.story.as_ref(), self.link_preview.as_ref()) {
+                (None, _) => {
+                    self.update(Msg::FetchStory);
+                }
+                // stored previews are the only ones rendered by the server
+                (Some(_), None) => self.link.send_message(Msg::FetchLinkPreview),
+                (Some(_), Some(_)) => {}
+            }
         }
     }
 
@@ -172,28 +221,26 @@ impl Component for Index {
         }
 
         if let Some(story) = self.story.clone() {
+            let image_url = self
+                .link_preview
+                .as_ref()
+                .and_then(|link_preview| link_preview.image_url.as_deref());
+
             return html! {
                 <section id="story-page">
-                    <header id="story-header">
-                        <RouterAnchor<AppRoute> route=AppRoute::Home classes="router,active">
-                            <strong class="action-button go-back-to-stream">
-                                <figure>
-                                    <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 16 16">
-                                        <path fill-rule="evenodd" d="M15 2a1 1 0 0 0-1-1H2a1 1 0 0 0-1 1v12a1 1 0 0 0 1 1h12a1 1 0 0 0 1-1V2zM0 2a2 2 0 0 1 2-2h12a2 2 0 0 1 2 2v12a2 2 0 0 1-2 2H2a2 2 0 0 1-2-2V2zm11.5 5.5a.5.5 0 0 1 0 1H5.707l2.147 2.146a.5.5 0 0 1-.708.708l-3-3a.5.5 0 0 1 0-.708l3-3a.5.5 0 1 1 .708.708L5.707 7.5H11.5z"/>
-                                    </svg>
-                                </figure>
-                                {"Story Stream"}
-                            </strong>
-                        </RouterAnchor<AppRoute>>
-                        <strong class="story-id">
-                            {format!("#{}", story.id)}
-                        </strong>
-                    </header>
+                    { views::story_page_header(story.id, router_link) }
                     <main class="story-content">
-                        {self.render_story_image()}
-                        <h2 class="story-title">{story.title.clone()}</h2>
+                        { views::story_heading(&story.title, image_url) }
+                        {
+                            if story.r#type == Type::Poll {
+                                html! { <PollChart id=story.id /> }
+                            } else {
+                                Html::default()
+                            }
+                        }
                         <hr />
-                        <Comments id=story.id />
+                        {self.render_tabs(&story)}
+                        {self.render_tab(&story)}
                     </main>
                 </section>
             };
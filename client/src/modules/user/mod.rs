@@ -0,0 +1,241 @@
+use anyhow::Error;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use common::hacker_news::{Item, Type, UserProfile};
+use common::html::ITEM_TEXT;
+use yew::format::{Json, Nothing};
+use yew::prelude::*;
+use yew::services::fetch::{FetchOptions, FetchTask, Request, Response};
+use yew::services::FetchService;
+use yew::web_sys::RequestMode;
+use yew_router::components::RouterAnchor;
+
+use crate::components::raw_html::RawHtml;
+use crate::constants::api;
+use crate::router::AppRoute;
+use crate::utils::html::sanitize;
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct Props {
+    pub id: String,
+}
+
+pub struct Index {
+    props: Props,
+    link: ComponentLink<Self>,
+    is_loading: bool,
+    is_loading_more_items: bool,
+    profile: Option<UserProfile>,
+    fetch_task: Option<FetchTask>,
+    error_message: Option<String>,
+}
+
+pub enum Msg {
+    FetchProfile(usize),
+    FetchSucced(UserProfile),
+    FetchFailed(String),
+}
+
+impl Index {
+    fn format_time(time: u64) -> String {
+        let naive = NaiveDateTime::from_timestamp(time as i64, 0);
+        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
+
+        datetime.format("%Y-%m-%d at %H:%M").to_string()
+    }
+
+    fn render_item(item: &Item) -> Html {
+        match item.r#type {
+            Type::Comment | Type::PollOpt => {
+                if let Some(text) = item.text.clone() {
+                    return html! {
+                        <li class="user-item user-comment">
+                            <small>{Index::format_time(item.time)}</small>
+                            <RawHtml inner_html=sanitize(&text, &ITEM_TEXT) />
+                        </li>
+                    };
+                }
+
+                Html::default()
+            }
+            Type::Story | Type::Job | Type::Poll => {
+                if let Some(title) = item.title.clone() {
+                    return html! {
+                        <li class="user-item user-story">
+                            <small>{Index::format_time(item.time)}</small>
+                            <RouterAnchor<AppRoute> route=AppRoute::Story(item.id) classes="router,active">
+                                {title}
+                            </RouterAnchor<AppRoute>>
+                        </li>
+                    };
+                }
+
+                Html::default()
+            }
+        }
+    }
+
+    fn render_load_more_button(&self, profile: &UserProfile) -> Html {
+        let class_name = "action-button load-more-stories-button";
+
+        if !profile.has_more {
+            return Html::default();
+        }
+
+        if self.is_loading_more_items {
+            return html! {
+                <button disabled=true class=class_name>
+                    {"Loading Submissions"}
+                </button>
+            };
+        }
+
+        let next_page = profile.page + 1;
+
+        html! {
+            <button class=class_name onclick=self.link.callback(move |_| Msg::FetchProfile(next_page))>
+                {"Load more submissions"}
+            </button>
+        }
+    }
+}
+
+impl Component for Index {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Index {
+            props,
+            link,
+            is_loading: false,
+            is_loading_more_items: false,
+            profile: None,
+            fetch_task: None,
+            error_message: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FetchProfile(page) => {
+                if page == 0 {
+                    self.is_loading = true;
+                } else {
+                    self.is_loading_more_items = true;
+                }
+
+                self.error_message = None;
+
+                let request = Request::get(format!(
+                    "{}/{}?page={}",
+                    api::v1::USERS,
+                    self.props.id,
+                    page
+                ))
+                .body(Nothing)
+                .unwrap();
+                let callback =
+                    self.link
+                        .callback(|res: Response<Json<Result<UserProfile, Error>>>| {
+                            let Json(data) = res.into_body();
+
+                            match data {
+                                Ok(profile) => Msg::FetchSucced(profile),
+                                Err(err) => Msg::FetchFailed(err.to_string()),
+                            }
+                        });
+
+                let mut options = FetchOptions::default();
+
+                options.mode = Some(RequestMode::Cors);
+
+                let task = FetchService::fetch_with_options(request, options, callback).unwrap();
+
+                self.fetch_task = Some(task);
+            }
+            Msg::FetchSucced(profile) => {
+                if let Some(current_profile) = self.profile.as_mut() {
+                    current_profile.items.extend(profile.items);
+                    current_profile.page = profile.page;
+                    current_profile.has_more = profile.has_more;
+                } else {
+                    self.profile = Some(profile);
+                }
+
+                self.is_loading = false;
+                self.is_loading_more_items = false;
+            }
+            Msg::FetchFailed(error_message) => {
+                self.error_message = Some(error_message);
+                self.is_loading = false;
+                self.is_loading_more_items = false;
+            }
+        }
+
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props == props {
+            return false;
+        }
+
+        self.props = props;
+        self.profile = None;
+        self.update(Msg::FetchProfile(0))
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.update(Msg::FetchProfile(0));
+        }
+    }
+
+    fn view(&self) -> Html {
+        if self.is_loading {
+            return html! {
+                <h1>{"Fetching user!!!"}</h1>
+            };
+        }
+
+        if self.error_message.is_some() {
+            let error_message = self.error_message.clone();
+            let error_message = error_message.unwrap();
+
+            return html! {
+                <h1>{format!("Failed to fetch user with id: {}", self.props.id)}<br />{error_message}</h1>
+            };
+        }
+
+        if let Some(profile) = &self.profile {
+            let user = &profile.user;
+
+            return html! {
+                <section id="user-page">
+                    <header id="user-header">
+                        <h2>{user.id.clone()}</h2>
+                        <span class="user-field">{format!("{} karma", user.karma)}</span>
+                        <span class="user-field">{format!("Joined {}", Index::format_time(user.created))}</span>
+                    </header>
+                    {
+                        if let Some(about) = user.about.clone() {
+                            html! {
+                                <article class="user-about">
+                                    <RawHtml inner_html=sanitize(&about, &ITEM_TEXT) />
+                                </article>
+                            }
+                        } else {
+                            Html::default()
+                        }
+                    }
+                    <ul id="user-items">
+                        { for profile.items.iter().map(Index::render_item) }
+                    </ul>
+                    { self.render_load_more_button(profile) }
+                </section>
+            };
+        }
+
+        Html::default()
+    }
+}
@@ -1,3 +1,5 @@
+use common::views;
+use yew::prelude::*;
 use yew_router::prelude::*;
 
 /// Do note that the implementation generated by the derive macro for Switch will
@@ -9,6 +11,58 @@ use yew_router::prelude::*;
 pub enum AppRoute {
     #[to = "/stories/{id}"]
     Story(u64),
+    #[to = "/users/{id}"]
+    User(String),
+    /// The search query is URI encoded, see `AppRoute::search`
+    #[to = "/search/{query}"]
+    Search(String),
+    #[to = "/top"]
+    Top,
+    #[to = "/best"]
+    Best,
+    #[to = "/new"]
+    New,
+    #[to = "/ask"]
+    Ask,
+    #[to = "/show"]
+    Show,
+    #[to = "/job"]
+    Job,
     #[to = "/"]
     Home,
 }
+
+impl AppRoute {
+    pub fn search(query: &str) -> AppRoute {
+        AppRoute::Search(String::from(js_sys::encode_uri_component(query)))
+    }
+}
+
+/// Routes linked to by `common::views`, feeds out of `views::FEEDS` lead
+/// home.
+impl From<views::Route> for AppRoute {
+    fn from(route: views::Route) -> Self {
+        match route {
+            views::Route::Home => AppRoute::Home,
+            views::Route::Feed("top") => AppRoute::Top,
+            views::Route::Feed("best") => AppRoute::Best,
+            views::Route::Feed("new") => AppRoute::New,
+            views::Route::Feed("ask") => AppRoute::Ask,
+            views::Route::Feed("show") => AppRoute::Show,
+            views::Route::Feed("job") => AppRoute::Job,
+            views::Route::Feed(_) => AppRoute::Home,
+            views::Route::Story(id) => AppRoute::Story(id),
+            views::Route::User(id) => AppRoute::User(id),
+        }
+    }
+}
+
+/// Links of `common::views`, followed by the router instead of loading the
+/// app again.
+pub fn router_link(route: views::Route, classes: &'static str, children: Html) -> Html {
+    html! {
+        <RouterAnchor<AppRoute> route=AppRoute::from(route) classes=classes>
+            {children}
+        </RouterAnchor<AppRoute>>
+    }
+}
@@ -0,0 +1,12 @@
+use common::html::{self, Policy, SanitizedHtml};
+use yew_router::route::Route;
+
+use crate::router::AppRoute;
+
+/// Sanitizes HTML to be rendered with `RawHtml`, links to HackerNews items
+/// are rewritten to their `AppRoute::Story`.
+pub fn sanitize(text: &str, policy: &Policy) -> SanitizedHtml {
+    html::sanitize(text, policy, &|id| {
+        Route::<()>::from(AppRoute::Story(id)).route
+    })
+}
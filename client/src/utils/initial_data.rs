@@ -0,0 +1,54 @@
+use anyhow::Error;
+use common::hacker_news::Story;
+use common::{InitialData, LinkPreview, Page};
+use yew::format::Json;
+use yew::utils::document;
+
+/// Id of the script the server embeds the data of rendered pages in
+const INITIAL_DATA_ID: &str = "initial-data";
+
+/// Id of the markup rendered by the server, replaced by the app once it's
+/// rendered
+const SERVER_RENDERED_ID: &str = "ssr";
+
+/// Stories the server rendered the stream of `feed` with, if it did.
+pub fn take_stream(feed: &str) -> Option<Page<Story>> {
+    take(|initial_data| match initial_data {
+        InitialData::Stream {
+            feed: rendered,
+            page,
+        } if rendered == feed => Some(*page),
+        _ => None,
+    })
+}
+
+/// Story the server rendered its page with, along with its preview, if it
+/// did.
+pub fn take_story(id: u64) -> Option<(Story, Option<LinkPreview>)> {
+    take(|initial_data| match initial_data {
+        InitialData::Story(page) if page.story.id == id => Some((page.story, page.link_preview)),
+        _ => None,
+    })
+}
+
+/// Removes the markup rendered by the server, the app renders the same
+/// page on its own.
+pub fn remove_server_rendered() {
+    if let Some(element) = document().get_element_by_id(SERVER_RENDERED_ID) {
+        element.remove();
+    }
+}
+
+/// Data is only taken once by the page it was rendered for, so pages
+/// visited afterwards fetch their own.
+fn take<T>(select: impl FnOnce(InitialData) -> Option<T>) -> Option<T> {
+    let element = document().get_element_by_id(INITIAL_DATA_ID)?;
+    let Json(initial_data): Json<Result<InitialData, Error>> =
+        Json::from(Ok(element.text_content()?));
+
+    let selected = select(initial_data.ok()?)?;
+
+    element.remove();
+
+    Some(selected)
+}
@@ -1 +1,3 @@
+pub mod html;
+pub mod initial_data;
 pub mod url;
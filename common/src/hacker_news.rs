@@ -0,0 +1,643 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// Reasons for an `Item` not to be converted into a more specific type.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ConversionError {
+    #[error("Item {id} is missing the \"{field}\" field")]
+    MissingField { id: u64, field: &'static str },
+    #[error("Item {0} is deleted")]
+    Deleted(u64),
+    #[error("Item {0} is dead")]
+    Dead(u64),
+    #[error("Item {id} is of type {found:?}, expected one of {expected:?}")]
+    WrongType {
+        id: u64,
+        expected: Vec<Type>,
+        found: Type,
+    },
+}
+
+/// Item types
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Type {
+    Job,
+    Story,
+    Comment,
+    Poll,
+    #[serde(rename = "pollopt")]
+    PollOpt,
+}
+
+/// Stories, comments, jobs, Ask HNs and even polls are just items.
+/// They're identified by their ids, which are unique integers, and
+/// live under `/v0/item/<id>`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Item {
+    /// The item's unique id.
+    pub id: u64,
+    /// `true` if the item is deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<bool>,
+    /// The type of item. One of "job", "story", "comment", "poll", or
+    /// "pollopt".
+    pub r#type: Type,
+    /// The username of the item's author.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+    /// Creation date of the item, in Unix Time.
+    pub time: u64,
+    /// The comment, story or poll text. HTML.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// true if the item is dead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead: Option<bool>,
+    /// The comment's parent: either another comment or the relevant story.
+    pub parent: Option<u64>,
+    /// The pollopt's associated poll.
+    pub poll: Option<u64>,
+    /// The ids of the item's comments, in ranked display order.
+    pub kids: Option<Vec<u64>>,
+    /// The URL of the story.
+    pub url: Option<String>,
+    /// The story's score, or the votes for a pollopt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u32>,
+    /// The title of the story, poll or job. HTML.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// A list of related pollopts, in display order.
+    pub parts: Option<Vec<u64>>,
+    /// In the case of stories or polls, the total comment count.
+    pub descendants: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Story {
+    /// The item's unique id.
+    pub id: u64,
+    /// The type of item. One of "job", "story", "comment", "poll", or
+    /// "pollopt".
+    pub r#type: Type,
+    /// The username of the item's author.
+    pub by: String,
+    /// Creation date of the item, in Unix Time.
+    pub time: u64,
+    /// The ids of the item's comments, in ranked display order.
+    pub kids: Option<Vec<u64>>,
+    /// The URL of the story.
+    pub url: Option<String>,
+    /// The story's score, or the votes for a pollopt.
+    pub score: u32,
+    /// The title of the story, poll or job. HTML.
+    pub title: String,
+    /// In the case of stories or polls, the total comment count.
+    pub descendants: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Comment {
+    /// The item's unique id.
+    pub id: u64,
+    /// The type of item. One of "job", "story", "comment", "poll", or
+    /// "pollopt".
+    pub r#type: Type,
+    /// The username of the item's author.
+    pub by: Option<String>,
+    /// The ids of the item's comments, in ranked display order.
+    pub kids: Option<Vec<u64>>,
+    /// The comment's parent: either another comment or the relevant story.
+    pub parent: Option<u64>,
+    /// The comment, story or poll text. HTML.
+    pub text: Option<String>,
+    /// Creation date of the item, in Unix Time.
+    pub time: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Job {
+    /// The item's unique id.
+    pub id: u64,
+    /// The type of item. One of "job", "story", "comment", "poll", or
+    /// "pollopt".
+    pub r#type: Type,
+    /// The username of the item's author.
+    pub by: String,
+    /// Creation date of the item, in Unix Time.
+    pub time: u64,
+    /// The title of the story, poll or job. HTML.
+    pub title: String,
+    /// The job posting text. HTML.
+    pub text: Option<String>,
+    /// The URL of the job posting.
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Poll {
+    /// The item's unique id.
+    pub id: u64,
+    /// The type of item. One of "job", "story", "comment", "poll", or
+    /// "pollopt".
+    pub r#type: Type,
+    /// The username of the item's author.
+    pub by: String,
+    /// Creation date of the item, in Unix Time.
+    pub time: u64,
+    /// The comment, story or poll text. HTML.
+    pub text: Option<String>,
+    /// The ids of the item's comments, in ranked display order.
+    pub kids: Option<Vec<u64>>,
+    /// The story's score, or the votes for a pollopt.
+    pub score: u32,
+    /// The title of the story, poll or job. HTML.
+    pub title: String,
+    /// A list of related pollopts, in display order.
+    pub parts: Vec<u64>,
+    /// In the case of stories or polls, the total comment count.
+    pub descendants: Option<u64>,
+    /// The pollopts referenced by `parts`, in display order. Empty unless
+    /// resolved.
+    #[serde(default)]
+    pub options: Vec<PollOption>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PollOption {
+    /// The item's unique id.
+    pub id: u64,
+    /// The username of the item's author.
+    pub by: Option<String>,
+    /// The pollopt's associated poll.
+    pub poll: u64,
+    /// The pollopt text. HTML.
+    pub text: Option<String>,
+    /// The votes for the pollopt.
+    pub score: u32,
+    /// Creation date of the item, in Unix Time.
+    pub time: u64,
+}
+
+/// A comment along with its replies, every reply is a `CommentNode` as
+/// well which makes it possible to represent a whole discussion thread.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CommentNode {
+    /// The comment at this level of the thread.
+    pub comment: Comment,
+    /// Replies to the comment, in ranked display order.
+    pub replies: Vec<CommentNode>,
+}
+
+/// Users are identified by case-sensitive ids, and live under
+/// `/v0/user/<id>`. Only users that have public activity (comments or
+/// story submissions) on the site are available through the API.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct User {
+    /// The user's unique username. Case-sensitive.
+    pub id: String,
+    /// Creation date of the user, in Unix Time.
+    pub created: u64,
+    /// The user's karma.
+    pub karma: u64,
+    /// The user's optional self-description. HTML.
+    pub about: Option<String>,
+    /// List of the user's stories, polls and comments.
+    pub submitted: Option<Vec<u64>>,
+}
+
+/// A user along with a page of the items the user has submitted.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UserProfile {
+    pub user: User,
+    /// Submitted items for the requested page, most recent first.
+    pub items: Vec<Item>,
+    /// The requested page, starting from 0.
+    pub page: usize,
+    /// `true` if there are submitted items beyond the requested page.
+    pub has_more: bool,
+}
+
+/// Changes pushed to clients subscribed to the live stories stream.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// A story which just made it to the `newstories` feed.
+    NewStory { story: Story },
+    /// A story which received votes or comments.
+    StoryUpdated {
+        id: u64,
+        score: u32,
+        descendants: Option<u64>,
+    },
+}
+
+/// Changes to a story's comment thread pushed to clients watching it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ThreadEvent {
+    /// A reply to the story or to one of its comments, `position` is the
+    /// index of the comment among its siblings.
+    CommentAdded { comment: Comment, position: usize },
+    /// A comment which text changed.
+    CommentEdited { comment: Comment },
+    /// A comment which was deleted, killed or removed from the thread.
+    CommentDeleted { id: u64 },
+}
+
+/// An item matching a full-text search, `snippet` holds the matching
+/// fragments of the item's title and text with matches wrapped in `<mark>`
+/// tags.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SearchHit {
+    /// The item's unique id.
+    pub id: u64,
+    /// The type of item. One of "job", "story", "comment", "poll", or
+    /// "pollopt".
+    pub r#type: Type,
+    /// The username of the item's author.
+    pub by: Option<String>,
+    /// Creation date of the item, in Unix Time.
+    pub time: u64,
+    /// The title of the story, poll or job. HTML.
+    pub title: Option<String>,
+    /// The URL of the story.
+    pub url: Option<String>,
+    /// The story's score, or the votes for a pollopt.
+    pub score: Option<u32>,
+    /// The comment's parent: either another comment or the relevant story.
+    pub parent: Option<u64>,
+    /// Matching fragments of the item's title and text. HTML.
+    pub snippet: String,
+}
+
+impl Item {
+    /// Ensures the item is neither deleted nor dead and is one of the
+    /// `expected` types.
+    fn check(&self, expected: &[Type]) -> Result<(), ConversionError> {
+        if self.deleted.unwrap_or(false) {
+            return Err(ConversionError::Deleted(self.id));
+        }
+
+        if self.dead.unwrap_or(false) {
+            return Err(ConversionError::Dead(self.id));
+        }
+
+        if !expected.contains(&self.r#type) {
+            return Err(ConversionError::WrongType {
+                id: self.id,
+                expected: expected.to_vec(),
+                found: self.r#type.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the value of a required field or the `MissingField` error.
+fn required<T>(id: u64, field: &'static str, value: Option<T>) -> Result<T, ConversionError> {
+    value.ok_or(ConversionError::MissingField { id, field })
+}
+
+/// Stories are the items listed in feeds, which could be job postings or
+/// polls as well.
+impl TryFrom<Item> for Story {
+    type Error = ConversionError;
+
+    fn try_from(value: Item) -> Result<Self, Self::Error> {
+        value.check(&[Type::Story, Type::Job, Type::Poll])?;
+
+        let id = value.id;
+
+        Ok(Story {
+            id,
+            r#type: value.r#type,
+            by: required(id, "by", value.by)?,
+            time: value.time,
+            kids: value.kids,
+            url: value.url,
+            score: required(id, "score", value.score)?,
+            title: required(id, "title", value.title)?,
+            descendants: value.descendants,
+        })
+    }
+}
+
+impl TryFrom<Item> for Job {
+    type Error = ConversionError;
+
+    fn try_from(value: Item) -> Result<Self, Self::Error> {
+        value.check(&[Type::Job])?;
+
+        let id = value.id;
+
+        Ok(Job {
+            id,
+            r#type: value.r#type,
+            by: required(id, "by", value.by)?,
+            time: value.time,
+            title: required(id, "title", value.title)?,
+            text: value.text,
+            // job postings without an external link come with an empty URL
+            url: value.url.filter(|url| !url.is_empty()),
+        })
+    }
+}
+
+impl TryFrom<Item> for Poll {
+    type Error = ConversionError;
+
+    fn try_from(value: Item) -> Result<Self, Self::Error> {
+        value.check(&[Type::Poll])?;
+
+        let id = value.id;
+
+        Ok(Poll {
+            id,
+            r#type: value.r#type,
+            by: required(id, "by", value.by)?,
+            time: value.time,
+            text: value.text,
+            kids: value.kids,
+            score: value.score.unwrap_or(0),
+            title: required(id, "title", value.title)?,
+            parts: value.parts.unwrap_or_default(),
+            descendants: value.descendants,
+            options: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<Item> for PollOption {
+    type Error = ConversionError;
+
+    fn try_from(value: Item) -> Result<Self, Self::Error> {
+        value.check(&[Type::PollOpt])?;
+
+        let id = value.id;
+
+        Ok(PollOption {
+            id,
+            by: value.by,
+            poll: required(id, "poll", value.poll)?,
+            text: value.text,
+            score: value.score.unwrap_or(0),
+            time: value.time,
+        })
+    }
+}
+
+impl TryFrom<Item> for Comment {
+    type Error = ConversionError;
+
+    fn try_from(value: Item) -> Result<Self, Self::Error> {
+        value.check(&[Type::Comment])?;
+
+        Ok(Comment {
+            id: value.id,
+            r#type: value.r#type,
+            by: value.by,
+            time: value.time,
+            kids: value.kids,
+            text: value.text,
+            parent: value.parent,
+        })
+    }
+}
+
+impl Story {
+    /// Stands for a story which is not available (deleted or dead) so
+    /// listings keep their positions.
+    pub fn placeholder(item: &Item) -> Self {
+        Story {
+            id: item.id,
+            r#type: item.r#type.clone(),
+            by: item.by.clone().unwrap_or_default(),
+            time: item.time,
+            kids: None,
+            url: None,
+            score: item.score.unwrap_or(0),
+            title: String::from(Item::placeholder_text(item)),
+            descendants: item.descendants,
+        }
+    }
+}
+
+impl Comment {
+    /// Stands for a comment which is not available (deleted or dead) so its
+    /// replies remain in place within a thread.
+    pub fn placeholder(item: &Item) -> Self {
+        Comment {
+            id: item.id,
+            r#type: Type::Comment,
+            by: None,
+            kids: item.kids.clone(),
+            parent: item.parent,
+            text: Some(String::from(Item::placeholder_text(item))),
+            time: item.time,
+        }
+    }
+}
+
+impl PollOption {
+    /// Stands for an option which is not available so the rest of its poll
+    /// can still be shown.
+    pub fn placeholder(item: &Item, poll: u64) -> Self {
+        PollOption {
+            id: item.id,
+            by: None,
+            poll,
+            text: Some(String::from(Item::placeholder_text(item))),
+            score: item.score.unwrap_or(0),
+            time: item.time,
+        }
+    }
+}
+
+impl Item {
+    /// Stands for an item which couldn't be fetched, placeholders built out
+    /// of it read as unavailable.
+    pub fn unavailable(id: u64, r#type: Type) -> Self {
+        Item {
+            id,
+            deleted: None,
+            r#type,
+            by: None,
+            time: 0,
+            text: None,
+            dead: None,
+            parent: None,
+            poll: None,
+            kids: None,
+            url: None,
+            score: None,
+            title: None,
+            parts: None,
+            descendants: None,
+        }
+    }
+
+    fn placeholder_text(item: &Item) -> &'static str {
+        if item.deleted.unwrap_or(false) {
+            "[deleted]"
+        } else if item.dead.unwrap_or(false) {
+            "[dead]"
+        } else {
+            "[unavailable]"
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use super::{Comment, ConversionError, Item, Job, LiveEvent, Poll, PollOption, Story, Type};
+
+    fn fixture(name: &str) -> Item {
+        let json = match name {
+            "story" => include_str!("../fixtures/items/story.json"),
+            "ask" => include_str!("../fixtures/items/ask.json"),
+            "job" => include_str!("../fixtures/items/job.json"),
+            "poll" => include_str!("../fixtures/items/poll.json"),
+            "pollopt" => include_str!("../fixtures/items/pollopt.json"),
+            "comment" => include_str!("../fixtures/items/comment.json"),
+            "deleted_comment" => include_str!("../fixtures/items/deleted_comment.json"),
+            "dead_story" => include_str!("../fixtures/items/dead_story.json"),
+            "untitled_story" => include_str!("../fixtures/items/untitled_story.json"),
+            _ => unreachable!(),
+        };
+
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn converts_a_story() {
+        let story = Story::try_from(fixture("story")).unwrap();
+
+        assert_eq!(story.id, 8863);
+        assert_eq!(story.by, "dhouston");
+        assert_eq!(story.score, 111);
+    }
+
+    #[test]
+    fn converts_an_ask_story_without_url() {
+        let story = Story::try_from(fixture("ask")).unwrap();
+
+        assert_eq!(story.title, "Ask HN: The Arc Effect");
+        assert_eq!(story.url, None);
+    }
+
+    #[test]
+    fn converts_a_job() {
+        let job = Job::try_from(fixture("job")).unwrap();
+
+        assert_eq!(job.by, "justin");
+        assert_eq!(job.url, None);
+    }
+
+    #[test]
+    fn converts_a_poll() {
+        let poll = Poll::try_from(fixture("poll")).unwrap();
+
+        assert_eq!(poll.parts, vec![126810, 126811]);
+        assert!(poll.options.is_empty());
+    }
+
+    #[test]
+    fn converts_a_pollopt() {
+        let option = PollOption::try_from(fixture("pollopt")).unwrap();
+
+        assert_eq!(option.poll, 126809);
+        assert_eq!(option.score, 335);
+    }
+
+    #[test]
+    fn converts_a_comment() {
+        let comment = Comment::try_from(fixture("comment")).unwrap();
+
+        assert_eq!(comment.by, Some(String::from("BrandonM")));
+        assert_eq!(comment.parent, Some(8863));
+    }
+
+    #[test]
+    fn fails_to_convert_a_deleted_item() {
+        assert_eq!(
+            Comment::try_from(fixture("deleted_comment")),
+            Err(ConversionError::Deleted(2921983))
+        );
+    }
+
+    #[test]
+    fn fails_to_convert_a_dead_item() {
+        assert_eq!(
+            Story::try_from(fixture("dead_story")),
+            Err(ConversionError::Dead(27925600))
+        );
+    }
+
+    #[test]
+    fn fails_to_convert_an_item_missing_required_fields() {
+        assert_eq!(
+            Story::try_from(fixture("untitled_story")),
+            Err(ConversionError::MissingField {
+                id: 1001,
+                field: "title"
+            })
+        );
+    }
+
+    #[test]
+    fn fails_to_convert_an_item_of_another_type() {
+        assert_eq!(
+            Comment::try_from(fixture("story")),
+            Err(ConversionError::WrongType {
+                id: 8863,
+                expected: vec![Type::Comment],
+                found: Type::Story,
+            })
+        );
+        assert!(Story::try_from(fixture("comment")).is_err());
+        assert!(Job::try_from(fixture("poll")).is_err());
+        assert!(PollOption::try_from(fixture("poll")).is_err());
+    }
+
+    #[test]
+    fn builds_placeholders_for_unavailable_items() {
+        let comment = Comment::placeholder(&fixture("deleted_comment"));
+        let story = Story::placeholder(&fixture("dead_story"));
+
+        assert_eq!(comment.text, Some(String::from("[deleted]")));
+        assert_eq!(comment.kids, Some(vec![2922097]));
+        assert_eq!(story.title, "[dead]");
+    }
+
+    #[test]
+    fn builds_placeholders_for_items_which_failed_to_be_fetched() {
+        let story = Story::placeholder(&Item::unavailable(1, Type::Story));
+        let comment = Comment::placeholder(&Item::unavailable(2, Type::Comment));
+        let option = PollOption::placeholder(&Item::unavailable(3, Type::PollOpt), 4);
+
+        assert_eq!(story.id, 1);
+        assert_eq!(story.title, "[unavailable]");
+        assert_eq!(comment.text, Some(String::from("[unavailable]")));
+        assert_eq!(option.poll, 4);
+        assert_eq!(option.text, Some(String::from("[unavailable]")));
+    }
+
+    #[test]
+    fn tags_live_events_with_their_kind() {
+        let event = LiveEvent::StoryUpdated {
+            id: 8863,
+            score: 111,
+            descendants: Some(71),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["kind"], "story_updated");
+        assert_eq!(serde_json::from_value::<LiveEvent>(json).unwrap(), event);
+    }
+}
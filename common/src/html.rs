@@ -0,0 +1,519 @@
+//! Sanitization of the HTML found in HackerNews items and articles before it
+//! is rendered. Elements out of the allow-list of a `Policy` are unwrapped,
+//! so their text is kept, while scripts and styles are dropped along with
+//! their content. Only `href` on links and `src` and `alt` on images are
+//! kept, other attributes are dropped.
+
+/// Elements dropped along with their content.
+const DROPPED_ELEMENTS: &[&str] = &[
+    "iframe", "noscript", "script", "style", "template", "textarea", "title",
+];
+
+/// Elements without content, these are never closed.
+const VOID_ELEMENTS: &[&str] = &["br", "hr", "img"];
+
+/// Elements closing an open paragraph, as HackerNews opens paragraphs
+/// without closing them.
+const CLOSING_PARAGRAPH: &[&str] = &[
+    "blockquote",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "ol",
+    "p",
+    "pre",
+    "table",
+    "ul",
+];
+
+/// Elements written out by `sanitize`, the rest are unwrapped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Policy {
+    pub elements: &'static [&'static str],
+}
+
+/// Text of comments, stories, jobs, polls and user profiles, the markup
+/// HackerNews itself writes.
+pub const ITEM_TEXT: Policy = Policy {
+    elements: &["a", "code", "i", "p", "pre"],
+};
+
+/// Search snippets, matches are wrapped in `mark` by the server.
+pub const SNIPPET: Policy = Policy {
+    elements: &["mark"],
+};
+
+/// Articles extracted by the server for the reader.
+pub const ARTICLE: Policy = Policy {
+    elements: &[
+        "a",
+        "b",
+        "blockquote",
+        "br",
+        "code",
+        "em",
+        "figcaption",
+        "figure",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "hr",
+        "i",
+        "img",
+        "li",
+        "ol",
+        "p",
+        "pre",
+        "strong",
+        "sub",
+        "sup",
+        "table",
+        "tbody",
+        "td",
+        "th",
+        "thead",
+        "tr",
+        "ul",
+    ],
+};
+
+/// HTML which went through `sanitize`, so it's safe to be set as the inner
+/// HTML of an element. It can't be built otherwise.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SanitizedHtml(String);
+
+impl SanitizedHtml {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+/// A start or end tag, names are lowercase.
+#[derive(Debug, PartialEq)]
+struct Tag {
+    name: String,
+    is_end: bool,
+    attributes: Vec<(String, String)>,
+}
+
+/// Sanitizes `html` with the allow-list of `policy`. Links to HackerNews
+/// items are rewritten to the path `story_path` gives for their id, other
+/// links are kept if these are http or https and marked as `nofollow`.
+pub fn sanitize(html: &str, policy: &Policy, story_path: &dyn Fn(u64) -> String) -> SanitizedHtml {
+    let mut output = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        output.push_str(&escape_text(&rest[..start]));
+        rest = &rest[start..];
+
+        // comments, doctypes and processing instructions
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = if rest.starts_with("<!--") {
+                rest.find("-->").map(|end| end + 3)
+            } else {
+                rest.find('>').map(|end| end + 1)
+            };
+
+            rest = &rest[end.unwrap_or(rest.len())..];
+            continue;
+        }
+
+        let (tag, length) = match parse_tag(&rest[1..]) {
+            Some(parsed) => parsed,
+            None => {
+                output.push_str("&lt;");
+                rest = &rest[1..];
+                continue;
+            }
+        };
+
+        rest = &rest[1 + length..];
+
+        if DROPPED_ELEMENTS.contains(&tag.name.as_str()) {
+            if !tag.is_end {
+                rest = skip_content(rest, &tag.name);
+            }
+
+            continue;
+        }
+
+        if !policy.elements.contains(&tag.name.as_str()) {
+            continue;
+        }
+
+        if tag.is_end {
+            if let Some(position) = open.iter().rposition(|name| *name == tag.name) {
+                for name in open.drain(position..).rev() {
+                    output.push_str(&format!("</{}>", name));
+                }
+            }
+
+            continue;
+        }
+
+        if CLOSING_PARAGRAPH.contains(&tag.name.as_str()) {
+            if let Some(position) = open.iter().rposition(|name| name == "p") {
+                for name in open.drain(position..).rev() {
+                    output.push_str(&format!("</{}>", name));
+                }
+            }
+        }
+
+        output.push_str(&start_tag(&tag, story_path));
+
+        if !VOID_ELEMENTS.contains(&tag.name.as_str()) {
+            open.push(tag.name);
+        }
+    }
+
+    output.push_str(&escape_text(rest));
+
+    for name in open.iter().rev() {
+        output.push_str(&format!("</{}>", name));
+    }
+
+    SanitizedHtml(output)
+}
+
+/// Writes the start tag of an allowed element with the attributes which
+/// are kept.
+fn start_tag(tag: &Tag, story_path: &dyn Fn(u64) -> String) -> String {
+    let attribute = |name: &str| {
+        tag.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.trim())
+    };
+
+    match tag.name.as_str() {
+        "a" => match attribute("href") {
+            Some(href) => match item_id(href) {
+                Some(id) => format!(r#"<a href="{}">"#, escape(&story_path(id))),
+                None if is_http(href) => {
+                    format!(r#"<a href="{}" rel="nofollow noopener">"#, escape(href))
+                }
+                None => String::from("<a>"),
+            },
+            None => String::from("<a>"),
+        },
+        "img" => match attribute("src").filter(|src| is_http(src)) {
+            Some(src) => format!(
+                r#"<img src="{}" alt="{}">"#,
+                escape(src),
+                escape(attribute("alt").unwrap_or_default())
+            ),
+            None => String::new(),
+        },
+        name => format!("<{}>", name),
+    }
+}
+
+/// Parses the tag at the start of `input`, which follows a `<`, along with
+/// its length up to and including its `>`.
+fn parse_tag(input: &str) -> Option<(Tag, usize)> {
+    let bytes = input.as_bytes();
+    let is_end = bytes.first() == Some(&b'/');
+    let mut index = if is_end { 1 } else { 0 };
+
+    if !bytes.get(index)?.is_ascii_alphabetic() {
+        return None;
+    }
+
+    let name_start = index;
+
+    while index < bytes.len() && bytes[index].is_ascii_alphanumeric() {
+        index += 1;
+    }
+
+    let name = input[name_start..index].to_ascii_lowercase();
+    let mut attributes = Vec::new();
+
+    loop {
+        while index < bytes.len() && (bytes[index].is_ascii_whitespace() || bytes[index] == b'/') {
+            index += 1;
+        }
+
+        match bytes.get(index)? {
+            b'>' => break,
+            _ => {
+                let attribute_start = index;
+
+                while index < bytes.len()
+                    && !bytes[index].is_ascii_whitespace()
+                    && !matches!(bytes[index], b'=' | b'>' | b'/')
+                {
+                    index += 1;
+                }
+
+                let attribute = input[attribute_start..index].to_ascii_lowercase();
+                let mut value = "";
+
+                if bytes.get(index) == Some(&b'=') {
+                    index += 1;
+
+                    match bytes.get(index)? {
+                        quote @ b'"' | quote @ b'\'' => {
+                            let length = input[index + 1..].find(*quote as char)?;
+
+                            value = &input[index + 1..index + 1 + length];
+                            index += length + 2;
+                        }
+                        _ => {
+                            let value_start = index;
+
+                            while index < bytes.len()
+                                && !bytes[index].is_ascii_whitespace()
+                                && bytes[index] != b'>'
+                            {
+                                index += 1;
+                            }
+
+                            value = &input[value_start..index];
+                        }
+                    }
+                }
+
+                attributes.push((attribute, decode_entities(value)));
+            }
+        }
+    }
+
+    Some((
+        Tag {
+            name,
+            is_end,
+            attributes,
+        },
+        index + 1,
+    ))
+}
+
+/// Skips the content of an element up to and including its end tag, all of
+/// it is skipped if the element isn't closed.
+fn skip_content<'a>(input: &'a str, name: &str) -> &'a str {
+    let end_tag = format!("</{}", name);
+    let lowercase = input.to_ascii_lowercase();
+
+    match lowercase.find(&end_tag) {
+        Some(start) => match input[start..].find('>') {
+            Some(end) => &input[start + end + 1..],
+            None => "",
+        },
+        None => "",
+    }
+}
+
+/// Id of the HackerNews item `href` links to, if it does.
+fn item_id(href: &str) -> Option<u64> {
+    let rest = href
+        .strip_prefix("https://")
+        .or_else(|| href.strip_prefix("http://"))?;
+    let query = rest.strip_prefix("news.ycombinator.com/item?")?;
+    let query = query.split('#').next()?;
+
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("id="))
+        .and_then(|id| id.parse().ok())
+}
+
+fn is_http(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Escapes text, entities found in it are kept as these are.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for (index, character) in text.char_indices() {
+        match character {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' if !starts_with_entity(&text[index..]) => escaped.push_str("&amp;"),
+            character => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes text to be written as the content or a quoted attribute of an
+/// element, in HTML as well as in XML.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+/// Whether `text` starts with a character reference, as in `&amp;` or
+/// `&#x2F;`.
+fn starts_with_entity(text: &str) -> bool {
+    let name = match text[1..].find(';') {
+        Some(end) => &text[1..1 + end],
+        None => return false,
+    };
+
+    match name.strip_prefix('#') {
+        Some(number) => match number
+            .strip_prefix('x')
+            .or_else(|| number.strip_prefix('X'))
+        {
+            Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+        },
+        None => !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
+/// Decodes the character references in an attribute value, HackerNews
+/// writes slashes in links as `&#x2F;`. Unknown named references are kept
+/// as they are.
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find(';') {
+            Some(end) if starts_with_entity(rest) => end,
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let name = &rest[1..end];
+        let character = match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => name.strip_prefix('#').and_then(|number| {
+                let code = match number
+                    .strip_prefix('x')
+                    .or_else(|| number.strip_prefix('X'))
+                {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                };
+
+                code.and_then(std::char::from_u32)
+            }),
+        };
+
+        match character {
+            Some(character) => decoded.push(character),
+            None => decoded.push_str(&rest[..=end]),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::{sanitize, Policy, ARTICLE, ITEM_TEXT, SNIPPET};
+
+    fn sanitize_with(html: &str, policy: &Policy) -> String {
+        sanitize(html, policy, &|id| format!("/stories/{}", id))
+            .as_str()
+            .to_string()
+    }
+
+    #[test]
+    fn keeps_the_markup_of_hacker_news() {
+        assert_eq!(
+            sanitize_with(
+                "It&#x27;s <i>fast</i><p>See <code>cargo build</code><p><pre><code>  fn main() {}</code></pre>",
+                &ITEM_TEXT
+            ),
+            "It&#x27;s <i>fast</i><p>See <code>cargo build</code></p><p></p><pre><code>  fn main() {}</code></pre>"
+        );
+    }
+
+    #[test]
+    fn rewrites_links_to_hacker_news_items() {
+        assert_eq!(
+            sanitize_with(
+                r#"<a href="https:&#x2F;&#x2F;news.ycombinator.com&#x2F;item?id=8863" rel="nofollow">parent</a>"#,
+                &ITEM_TEXT
+            ),
+            r#"<a href="/stories/8863">parent</a>"#
+        );
+    }
+
+    #[test]
+    fn marks_external_links_as_nofollow() {
+        assert_eq!(
+            sanitize_with(
+                r#"<a href="https://example.com/?a=1&amp;b=2" target="_blank" onclick="steal()">x</a>"#,
+                &ITEM_TEXT
+            ),
+            r#"<a href="https://example.com/?a=1&amp;b=2" rel="nofollow noopener">x</a>"#
+        );
+        assert_eq!(
+            sanitize_with(
+                r#"<a href="&#x6A;avascript:steal()">x</a><a href=" JaVaScRiPt:steal()">y</a>"#,
+                &ITEM_TEXT
+            ),
+            "<a>x</a><a>y</a>"
+        );
+    }
+
+    #[test]
+    fn leaves_out_elements_which_are_not_allowed() {
+        assert_eq!(
+            sanitize_with(
+                r#"<p onmouseover="steal()">Hi<script>document.write("<p>")</script><img src=x onerror=steal()><b>there</b></p><!-- <p> -->"#,
+                &ITEM_TEXT
+            ),
+            "<p>Hithere</p>"
+        );
+        assert_eq!(
+            sanitize_with("<mark>rust</mark> <i>is</i> 1 < 2", &SNIPPET),
+            "<mark>rust</mark> is 1 &lt; 2"
+        );
+    }
+
+    #[test]
+    fn closes_elements_left_open() {
+        assert_eq!(
+            sanitize_with("<i><code>x</i> & y</code></p>", &ITEM_TEXT),
+            "<i><code>x</code></i> &amp; y"
+        );
+        assert_eq!(
+            sanitize_with("<pre>unterminated <a href=\"https://", &ITEM_TEXT),
+            "<pre>unterminated &lt;a href=\"https://</pre>"
+        );
+    }
+
+    #[test]
+    fn keeps_images_of_articles() {
+        assert_eq!(
+            sanitize_with(
+                r#"<figure><img src="https://example.com/a.png" alt="A &quot;crab&quot;" width="10"><img src="data:image/png;base64,AA"></figure>"#,
+                &ARTICLE
+            ),
+            r#"<figure><img src="https://example.com/a.png" alt="A &quot;crab&quot;"></figure>"#
+        );
+    }
+}
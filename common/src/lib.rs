@@ -1,144 +1,96 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct LinkPreview {
     pub title: Option<String>,
     pub image_url: Option<String>,
     pub description: Option<String>,
+    pub domain: Option<String>,
+    pub site_name: Option<String>,
+    pub favicon_url: Option<String>,
+    pub canonical_url: Option<String>,
+    /// Open Graph type of the page, as in `article` or `video.other`
+    pub content_type: Option<String>,
+    /// Unix timestamp of the time the page was published
+    pub published_at: Option<i64>,
+    /// Estimated minutes it takes to read the page
+    pub reading_time: Option<u32>,
+    pub embed: Option<Embed>,
 }
 
-pub mod hacker_news {
-    use serde::{Deserialize, Serialize};
-    use std::convert::TryFrom;
+/// Media a page is about, as described by oEmbed or a Twitter card
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Embed {
+    pub kind: EmbedKind,
+    pub provider_name: Option<String>,
+    pub author_name: Option<String>,
+    /// Page the media is played in, meant to be shown in an iframe
+    pub player_url: Option<String>,
+    /// Text of a post, as in the text of a tweet
+    pub text: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
 
-    /// Item types
-    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-    #[serde(rename_all = "snake_case")]
-    pub enum Type {
-        Job,
-        Story,
-        Comment,
-        Poll,
-        PollOpt,
-    }
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbedKind {
+    Video,
+    Post,
+}
 
-    /// Stories, comments, jobs, Ask HNs and even polls are just items.
-    /// They're identified by their ids, which are unique integers, and
-    /// live under `/v0/item/<id>`.
-    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-    pub struct Item {
-        /// The item's unique id.
-        pub id: u64,
-        /// `true` if the item is deleted.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub deleted: Option<bool>,
-        /// The type of item. One of "job", "story", "comment", "poll", or
-        /// "pollopt".
-        pub r#type: Type,
-        /// The username of the item's author.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub by: Option<String>,
-        /// Creation date of the item, in Unix Time.
-        pub time: u64,
-        /// The comment, story or poll text. HTML.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub text: Option<String>,
-        /// true if the item is dead.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub dead: Option<bool>,
-        /// The comment's parent: either another comment or the relevant story.
-        pub parent: Option<u64>,
-        /// The pollopt's associated poll.
-        pub poll: Option<u64>,
-        /// The ids of the item's comments, in ranked display order.
-        pub kids: Option<Vec<u64>>,
-        /// The URL of the story.
-        pub url: Option<String>,
-        /// The story's score, or the votes for a pollopt.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub score: Option<u32>,
-        /// The title of the story, poll or job. HTML.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub title: Option<String>,
-        /// A list of related pollopts, in display order.
-        pub parts: Option<Vec<u64>>,
-        /// In the case of stories or polls, the total comment count.
-        pub descendants: Option<u64>,
-    }
+/// Body of a request for the previews of many URLs at once, these are
+/// answered with a map of each URL to its `LinkPreview`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LinkPreviewBatch {
+    pub urls: Vec<String>,
+}
 
-    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-    pub struct Story {
-        /// The item's unique id.
-        pub id: u64,
-        /// The type of item. One of "job", "story", "comment", "poll", or
-        /// "pollopt".
-        pub r#type: Type,
-        /// The username of the item's author.
-        pub by: String,
-        /// Creation date of the item, in Unix Time.
-        pub time: u64,
-        /// The ids of the item's comments, in ranked display order.
-        pub kids: Option<Vec<u64>>,
-        /// The URL of the story.
-        pub url: Option<String>,
-        /// The story's score, or the votes for a pollopt.
-        pub score: u32,
-        /// The title of the story, poll or job. HTML.
-        pub title: String,
-        /// In the case of stories or polls, the total comment count.
-        pub descendants: Option<u64>,
-    }
+/// Main article of the page a story links to, for reading it without
+/// leaving the story.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Article {
+    pub url: String,
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    /// HTML of the article, sanitized by the server. Images are served
+    /// through the image proxy
+    pub content: String,
+    /// Plain text of the article, paragraphs are separated by blank lines
+    pub text: String,
+}
 
-    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-    pub struct Comment {
-        /// The item's unique id.
-        pub id: u64,
-        /// The type of item. One of "job", "story", "comment", "poll", or
-        /// "pollopt".
-        pub r#type: Type,
-        /// The username of the item's author.
-        pub by: Option<String>,
-        /// The ids of the item's comments, in ranked display order.
-        pub kids: Option<Vec<u64>>,
-        /// The comment's parent: either another comment or the relevant story.
-        pub parent: Option<u64>,
-        /// The comment, story or poll text. HTML.
-        pub text: Option<String>,
-        /// Creation date of the item, in Unix Time.
-        pub time: u64,
-    }
+/// Data a page was rendered with by the server, embedded in the page so the
+/// client starts from it instead of fetching it again.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InitialData {
+    Stream {
+        feed: String,
+        page: Box<Page<hacker_news::Story>>,
+    },
+    Story(Box<StoryPage>),
+}
 
-    impl TryFrom<Item> for Story {
-        type Error = anyhow::Error;
+/// Story a page was rendered for, along with the preview of the page it
+/// links to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct StoryPage {
+    pub story: hacker_news::Story,
+    pub link_preview: Option<LinkPreview>,
+}
 
-        fn try_from(value: Item) -> Result<Self, Self::Error> {
-            Ok(Story {
-                id: value.id,
-                r#type: value.r#type,
-                by: value.by.unwrap(),
-                time: value.time,
-                kids: value.kids,
-                url: value.url,
-                score: value.score.unwrap(),
-                title: value.title.unwrap(),
-                descendants: value.descendants,
-            })
-        }
-    }
+/// A page out of a paginated list, `next_cursor` is meant to be sent back as
+/// the `cursor` query param to fetch the page which follows this one.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
 
-    impl TryFrom<Item> for Comment {
-        type Error = anyhow::Error;
+pub mod hacker_news;
+pub mod html;
 
-        fn try_from(value: Item) -> Result<Self, Self::Error> {
-            Ok(Comment {
-                id: value.id,
-                r#type: value.r#type,
-                by: value.by,
-                time: value.time,
-                kids: value.kids,
-                text: value.text,
-                parent: value.parent,
-            })
-        }
-    }
-}
+#[cfg(feature = "views")]
+pub mod views;
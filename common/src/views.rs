@@ -0,0 +1,372 @@
+//! Views of the header, the stream and the top of the story page, shared by
+//! the client components and the pages the server renders so both write the
+//! same markup. Yew 0.18 only mounts components in the browser, so these are
+//! plain functions building `Html`: components render them as part of their
+//! own view, while the server writes them out as a string.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use yew::prelude::*;
+
+use crate::hacker_news::Story;
+use crate::{Embed, EmbedKind, LinkPreview};
+
+/// Feeds of the tabs in the header, as expected by the `feed` query param
+/// on the stories endpoint. Each one is found at `/{feed}`
+pub const FEEDS: [&str; 6] = ["top", "best", "new", "ask", "show", "job"];
+
+/// Pages within the app views link to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Route {
+    Home,
+    /// One of the `FEEDS`
+    Feed(&'static str),
+    Story(u64),
+    User(String),
+}
+
+impl Route {
+    pub fn path(&self) -> String {
+        match self {
+            Route::Home => String::from("/"),
+            Route::Feed(feed) => format!("/{}", feed),
+            Route::Story(id) => format!("/stories/{}", id),
+            Route::User(id) => format!("/users/{}", id),
+        }
+    }
+}
+
+/// Renders a link to a `Route` with the given classes around its children.
+/// The client renders a `RouterAnchor`, so links are followed without
+/// loading the app again, while the server renders an `anchor`.
+pub type Link = fn(Route, &'static str, Html) -> Html;
+
+/// A plain link to `route`, as the `RouterAnchor` of the client is rendered.
+pub fn anchor(route: Route, classes: &'static str, children: Html) -> Html {
+    html! {
+        <a class=classes href=route.path()>
+            {children}
+        </a>
+    }
+}
+
+/// A story of a stream along with the preview of the page it links to.
+pub struct StoryCard<'a> {
+    pub story: &'a Story,
+    pub preview: Option<&'a LinkPreview>,
+    /// Previews are fetched once the stream is rendered by the client
+    pub is_loading_preview: bool,
+}
+
+/// Branding of the header, the search form and the feed tabs follow it.
+pub fn header_branding() -> Html {
+    html! {
+        <div id="header-wrapper">
+            <div id="navigation">
+                <button style="display: none;">
+                    <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 16 16">
+                        <path fill-rule="evenodd" d="M2.5 12a.5.5 0 0 1 .5-.5h10a.5.5 0 0 1 0 1H3a.5.5 0 0 1-.5-.5zm0-4a.5.5 0 0 1 .5-.5h10a.5.5 0 0 1 0 1H3a.5.5 0 0 1-.5-.5zm0-4a.5.5 0 0 1 .5-.5h10a.5.5 0 0 1 0 1H3a.5.5 0 0 1-.5-.5z"/>
+                    </svg>
+                </button>
+            </div>
+            <div id="branding">
+                <h1>{"FluxCap"}</h1>
+                <small>{"Powered by HackerNews and Firebase"}</small>
+            </div>
+            <div id="user">
+                <button style="display: none;">
+                    <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 16 16">
+                        <path d="M11 6a3 3 0 1 1-6 0 3 3 0 0 1 6 0z"/>
+                        <path fill-rule="evenodd" d="M0 8a8 8 0 1 1 16 0A8 8 0 0 1 0 8zm8-7a7 7 0 0 0-5.468 11.37C3.242 11.226 4.805 10 8 10s4.757 1.225 5.468 2.37A7 7 0 0 0 8 1z"/>
+                    </svg>
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// A tab for each of the `FEEDS`.
+pub fn feed_tabs(link: Link) -> Html {
+    html! {
+        <nav id="feeds">
+            {
+                for FEEDS.iter().map(|&feed| {
+                    link(Route::Feed(feed), "feed-tab", Html::from(feed))
+                })
+            }
+        </nav>
+    }
+}
+
+/// A story of a stream, its description is the one of the preview of the
+/// page it links to.
+pub fn story_card(card: &StoryCard, link: Link) -> Html {
+    html! {
+        <li class="story">
+            <header>
+                { story_author(&card.story.by, link) }
+                { story_time(card.story.time) }
+            </header>
+            <main>
+                { card.preview.map(story_source).unwrap_or_default() }
+                <h2 class="story-title">{card.story.title.clone()}</h2>
+                <article>
+                    { story_description(card) }
+                </article>
+            </main>
+            <footer>
+                <div class="story-meta">
+                    { story_score(card.story.score) }
+                    { story_comments(card.story, link) }
+                </div>
+                { story_read_more(card.story) }
+            </footer>
+        </li>
+    }
+}
+
+/// Header of the page of story `id`, linking back to the stream.
+pub fn story_page_header(id: u64, link: Link) -> Html {
+    let go_back = html! {
+        <strong class="action-button go-back-to-stream">
+            <figure>
+                <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 16 16">
+                    <path fill-rule="evenodd" d="M15 2a1 1 0 0 0-1-1H2a1 1 0 0 0-1 1v12a1 1 0 0 0 1 1h12a1 1 0 0 0 1-1V2zM0 2a2 2 0 0 1 2-2h12a2 2 0 0 1 2 2v12a2 2 0 0 1-2 2H2a2 2 0 0 1-2-2V2zm11.5 5.5a.5.5 0 0 1 0 1H5.707l2.147 2.146a.5.5 0 0 1-.708.708l-3-3a.5.5 0 0 1 0-.708l3-3a.5.5 0 1 1 .708.708L5.707 7.5H11.5z"/>
+                </svg>
+            </figure>
+            {"Story Stream"}
+        </strong>
+    };
+
+    html! {
+        <header id="story-header">
+            { link(Route::Home, "router,active", go_back) }
+            <strong class="story-id">
+                {format!("#{}", id)}
+            </strong>
+        </header>
+    }
+}
+
+/// Image and title at the top of the page of a story, the image is the one
+/// of the preview of the page the story links to.
+pub fn story_heading(title: &str, image_url: Option<&str>) -> Html {
+    let image = match image_url {
+        // the proxy serves larger thumbnails for the top of the page
+        Some(image_url) => html! {
+            <figure class="illustration">
+                <img src=format!("{}?size=hero", image_url) alt="Story image" />
+            </figure>
+        },
+        None => Html::default(),
+    };
+
+    html! {
+        <>
+            {image}
+            <h2 class="story-title">{title.to_string()}</h2>
+        </>
+    }
+}
+
+fn story_author(by: &str, link: Link) -> Html {
+    html! {
+        <span class="story-field">
+            <figure>
+                <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" class="bi bi-person" viewBox="0 0 16 16">
+                    <path d="M8 8a3 3 0 1 0 0-6 3 3 0 0 0 0 6zm2-3a2 2 0 1 1-4 0 2 2 0 0 1 4 0zm4 8c0 1-1 1-1 1H3s-1 0-1-1 1-4 6-4 6 3 6 4zm-1-.004c-.001-.246-.154-.986-.832-1.664C11.516 10.68 10.289 10 8 10c-2.29 0-3.516.68-4.168 1.332-.678.678-.83 1.418-.832 1.664h10z"/>
+                </svg>
+            </figure>
+            { link(Route::User(by.to_string()), "author-link", Html::from(by)) }
+        </span>
+    }
+}
+
+fn story_time(time: u64) -> Html {
+    let naive = NaiveDateTime::from_timestamp(time as i64, 0);
+    let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
+
+    html! {
+        <span class="story-field">
+            <figure>
+                <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" class="bi bi-clock" viewBox="0 0 16 16">
+                    <path d="M8 3.5a.5.5 0 0 0-1 0V9a.5.5 0 0 0 .252.434l3.5 2a.5.5 0 0 0 .496-.868L8 8.71V3.5z"/>
+                    <path d="M8 16A8 8 0 1 0 8 0a8 8 0 0 0 0 16zm7-8A7 7 0 1 1 1 8a7 7 0 0 1 14 0z"/>
+                </svg>
+            </figure>
+            {datetime.format("%Y-%m-%d at %H:%M").to_string()}
+        </span>
+    }
+}
+
+fn story_score(score: u32) -> Html {
+    html! {
+        <span class="story-field story-score">
+            <figure>
+                <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" class="bi bi-arrow-up-circle" viewBox="0 0 16 16">
+                    <path fill-rule="evenodd" d="M1 8a7 7 0 1 0 14 0A7 7 0 0 0 1 8zm15 0A8 8 0 1 1 0 8a8 8 0 0 1 16 0zm-7.5 3.5a.5.5 0 0 1-1 0V5.707L5.354 7.854a.5.5 0 1 1-.708-.708l3-3a.5.5 0 0 1 .708 0l3 3a.5.5 0 0 1-.708.708L8.5 5.707V11.5z"/>
+                </svg>
+            </figure>
+            {score}
+        </span>
+    }
+}
+
+fn story_comments(story: &Story, link: Link) -> Html {
+    let kids = match story.kids.as_ref() {
+        Some(kids) => kids,
+        None => return Html::default(),
+    };
+    let count = html! {
+        <>
+            <figure>
+                <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" viewBox="0 0 16 16">
+                    <path d="M2.678 11.894a1 1 0 0 1 .287.801 10.97 10.97 0 0 1-.398 2c1.395-.323 2.247-.697 2.634-.893a1 1 0 0 1 .71-.074A8.06 8.06 0 0 0 8 14c3.996 0 7-2.807 7-6 0-3.192-3.004-6-7-6S1 4.808 1 8c0 1.468.617 2.83 1.678 3.894zm-.493 3.905a21.682 21.682 0 0 1-.713.129c-.2.032-.352-.176-.273-.362a9.68 9.68 0 0 0 .244-.637l.003-.01c.248-.72.45-1.548.524-2.319C.743 11.37 0 9.76 0 8c0-3.866 3.582-7 8-7s8 3.134 8 7-3.582 7-8 7a9.06 9.06 0 0 1-2.347-.306c-.52.263-1.639.742-3.468 1.105z"/>
+                </svg>
+            </figure>
+            {kids.len()}
+        </>
+    };
+
+    html! {
+        <span class="story-field action-button">
+            { link(Route::Story(story.id), "router,active", count) }
+        </span>
+    }
+}
+
+fn story_read_more(story: &Story) -> Html {
+    let url = match story.url.clone() {
+        Some(url) => url,
+        None => return Html::default(),
+    };
+
+    html! {
+        <span class="story-field action-button">
+            <a href=url class="read-more-button" target="_blank">
+                <figure>
+                    <svg xmlns="http://www.w3.org/2000/svg" fill="currentColor" class="bi bi-box-arrow-up-right" viewBox="0 0 16 16">
+                        <path fill-rule="evenodd" d="M8.636 3.5a.5.5 0 0 0-.5-.5H1.5A1.5 1.5 0 0 0 0 4.5v10A1.5 1.5 0 0 0 1.5 16h10a1.5 1.5 0 0 0 1.5-1.5V7.864a.5.5 0 0 0-1 0V14.5a.5.5 0 0 1-.5.5h-10a.5.5 0 0 1-.5-.5v-10a.5.5 0 0 1 .5-.5h6.636a.5.5 0 0 0 .5-.5z"/>
+                        <path fill-rule="evenodd" d="M16 .5a.5.5 0 0 0-.5-.5h-5a.5.5 0 0 0 0 1h3.793L6.146 9.146a.5.5 0 1 0 .708.708L15 1.707V5.5a.5.5 0 0 0 1 0v-5z"/>
+                    </svg>
+                </figure>
+                {"Read More"}
+            </a>
+        </span>
+    }
+}
+
+/// Site the story links to, along with the time it takes to read it
+fn story_source(preview: &LinkPreview) -> Html {
+    let name = preview.site_name.clone().or_else(|| preview.domain.clone());
+
+    if name.is_none() && preview.reading_time.is_none() {
+        return Html::default();
+    }
+
+    let favicon = match preview.favicon_url.as_ref() {
+        Some(favicon_url) => html! {
+            <img class="story-favicon" src=format!("{}?size=icon", favicon_url) alt="" />
+        },
+        None => Html::default(),
+    };
+    let published_at = match preview.published_at {
+        Some(published_at) => {
+            let naive = NaiveDateTime::from_timestamp(published_at, 0);
+            let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
+
+            html! {
+                <span>{datetime.format("%Y-%m-%d").to_string()}</span>
+            }
+        }
+        None => Html::default(),
+    };
+    let reading_time = match preview.reading_time {
+        Some(reading_time) => html! {
+            <span>{format!("{} min read", reading_time)}</span>
+        },
+        None => Html::default(),
+    };
+
+    html! {
+        <div class="story-source">
+            {favicon}
+            <span>{name.unwrap_or_default()}</span>
+            {published_at}
+            {reading_time}
+        </div>
+    }
+}
+
+fn story_description(card: &StoryCard) -> Html {
+    if card.is_loading_preview {
+        return html! {
+            <p>{"Loading Description"}</p>
+        };
+    }
+
+    let preview = match card.preview {
+        Some(preview) => preview,
+        None => return Html::default(),
+    };
+
+    if let Some(embed) = preview
+        .embed
+        .as_ref()
+        .and_then(|embed| story_embed(embed, &card.story.title))
+    {
+        return embed;
+    }
+
+    match (preview.description.clone(), preview.image_url.clone()) {
+        (Some(description), _) if description.is_empty() => html! {
+            <p class="empty-description-label">{"No description available"}</p>
+        },
+        (Some(description), Some(image_url)) => html! {
+            <p>
+                <figure>
+                    <img src=image_url alt=format!("{} story image", card.story.title) />
+                </figure>
+                {description}
+            </p>
+        },
+        (Some(description), None) => html! {
+            <p>{description}</p>
+        },
+        (None, _) => Html::default(),
+    }
+}
+
+/// Players of videos and the text of posts, as found by the server
+fn story_embed(embed: &Embed, title: &str) -> Option<Html> {
+    match embed.kind {
+        EmbedKind::Video => {
+            let player_url = embed.player_url.clone()?;
+
+            Some(html! {
+                <figure class="story-embed">
+                    <iframe
+                        src=player_url
+                        title=title.to_string()
+                        loading="lazy"
+                        allowfullscreen="true"
+                        sandbox="allow-scripts allow-same-origin allow-presentation"
+                    ></iframe>
+                </figure>
+            })
+        }
+        EmbedKind::Post => {
+            let text = embed.text.clone()?;
+            let author = match embed.author_name.clone() {
+                Some(author_name) => html! { <cite>{author_name}</cite> },
+                None => Html::default(),
+            };
+
+            Some(html! {
+                <blockquote class="story-post">
+                    <p>{text}</p>
+                    {author}
+                </blockquote>
+            })
+        }
+    }
+}
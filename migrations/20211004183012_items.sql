@@ -0,0 +1,23 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS items (
+  id BIGINT PRIMARY KEY,
+  type VARCHAR(16) NOT NULL,
+  author VARCHAR(255),
+  time BIGINT NOT NULL,
+  text TEXT,
+  deleted BOOLEAN NOT NULL DEFAULT FALSE,
+  dead BOOLEAN NOT NULL DEFAULT FALSE,
+  parent BIGINT,
+  poll BIGINT,
+  kids BIGINT[],
+  url TEXT,
+  score INTEGER,
+  title TEXT,
+  parts BIGINT[],
+  descendants BIGINT,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS items_parent_idx ON items (parent);
+CREATE INDEX IF NOT EXISTS items_author_idx ON items (author);
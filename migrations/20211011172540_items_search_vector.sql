@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE items
+ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+  setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+  setweight(to_tsvector('english', regexp_replace(coalesce(text, ''), '<[^>]*>', ' ', 'g')), 'B')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS items_search_vector_idx ON items USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS items_time_idx ON items (time);
@@ -0,0 +1,13 @@
+-- Previews are refreshed once these expire instead of being deleted by a
+-- trigger, failed fetches are stored as well so they are retried with backoff
+DROP TRIGGER IF EXISTS trigger_delete_old_previews ON previews;
+DROP FUNCTION IF EXISTS delete_old_previews();
+
+ALTER TABLE previews
+ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+ADD COLUMN failed BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN failures INTEGER NOT NULL DEFAULT 0;
+
+UPDATE previews SET expires_at = created_at + INTERVAL '2 days';
+
+CREATE INDEX IF NOT EXISTS previews_expires_at_idx ON previews (expires_at);
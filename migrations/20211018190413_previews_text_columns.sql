@@ -0,0 +1,7 @@
+-- Descriptions and image URLs of real pages often exceed 255 characters,
+-- which failed the whole upsert so these previews were never stored
+ALTER TABLE previews
+ALTER COLUMN title TYPE TEXT,
+ALTER COLUMN description TYPE TEXT,
+ALTER COLUMN domain TYPE TEXT,
+ALTER COLUMN image_url TYPE TEXT;
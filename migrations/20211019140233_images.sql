@@ -0,0 +1,25 @@
+-- Preview images are served through the image proxy, `url_hash` is the
+-- SHA-256 of the original URL as computed by `utils::sha256::hash`
+CREATE TABLE IF NOT EXISTS images (
+  url_hash TEXT PRIMARY KEY,
+  url TEXT NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS thumbnails (
+  url_hash TEXT NOT NULL REFERENCES images (url_hash) ON DELETE CASCADE,
+  size VARCHAR(16) NOT NULL,
+  content_type VARCHAR(64) NOT NULL,
+  width INTEGER NOT NULL,
+  height INTEGER NOT NULL,
+  data BYTEA NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (url_hash, size)
+);
+
+-- images of the previews stored so far
+INSERT INTO images (url_hash, url)
+SELECT DISTINCT upper(encode(sha256(convert_to(image_url, 'UTF8')), 'hex')), image_url
+FROM previews
+WHERE image_url IS NOT NULL
+ON CONFLICT (url_hash) DO NOTHING;
@@ -0,0 +1,13 @@
+-- Metadata found in pages beyond their title, description and image, `embed`
+-- holds the player or post described by oEmbed or a Twitter card
+ALTER TABLE previews
+ADD COLUMN site_name TEXT,
+ADD COLUMN favicon_url TEXT,
+ADD COLUMN canonical_url TEXT,
+ADD COLUMN content_type TEXT,
+ADD COLUMN published_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN reading_time INTEGER,
+ADD COLUMN embed JSONB;
+
+-- Stored previews are refreshed so these gain their metadata
+UPDATE previews SET expires_at = CURRENT_TIMESTAMP WHERE failed = FALSE;
@@ -0,0 +1,18 @@
+-- Articles extracted from the pages stories link to, `url_hash` is the
+-- SHA-256 of the page URL as computed by `utils::sha256::hash`. Pages
+-- without an article are stored as failed so they aren't fetched again
+-- until these expire
+CREATE TABLE IF NOT EXISTS articles (
+  url_hash TEXT PRIMARY KEY,
+  url TEXT NOT NULL,
+  title TEXT,
+  byline TEXT,
+  content TEXT NOT NULL DEFAULT '',
+  text TEXT NOT NULL DEFAULT '',
+  failed BOOLEAN NOT NULL DEFAULT FALSE,
+  expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS articles_expires_at_idx ON articles (expires_at);
@@ -0,0 +1,9 @@
+-- Images which fail to be fetched or decoded are tried again once
+-- `retry_at` passes, with backoff. Images are purged once no preview nor
+-- article registered them for a while, their thumbnails along with them
+ALTER TABLE images
+ADD COLUMN failures INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN retry_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN registered_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS images_registered_at_idx ON images (registered_at);
@@ -1,37 +1,104 @@
 use actix_web::web::Data;
 use sqlx::postgres::PgPool;
+use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
 
 use crate::environment::Environment;
-use crate::services::hacker_news::HackerNewsService;
+use crate::services::hacker_news::{
+    HackerNewsService, LiveUpdates, Mirror, Poller, ThreadWatcher, UnavailableItemPolicy,
+};
+use crate::services::images::ImageService;
 use crate::services::link_preview::LinkPreviewService;
+use crate::services::reader::ReaderService;
+use crate::services::search::SearchService;
 
 pub struct AppData {
-    pub hacker_news_service: Arc<Mutex<HackerNewsService>>,
-    pub link_preview_service: Arc<Mutex<LinkPreviewService>>,
+    pub hacker_news_service: Arc<HackerNewsService>,
+    pub image_service: Arc<ImageService>,
+    pub link_preview_service: Arc<LinkPreviewService>,
+    pub live_updates: Arc<LiveUpdates>,
+    pub reader_service: Arc<ReaderService>,
+    pub search_service: Arc<SearchService>,
+    pub thread_watcher: Arc<ThreadWatcher>,
     pub database_pool: Arc<PgPool>,
     environment: Arc<Environment>,
 }
 
 impl AppData {
     pub async fn new() -> Data<Self> {
-        let environment = Arc::new(Environment::new());
-        let database_pool = Arc::new(AppData::make_db_pool(Arc::clone(&environment)).await);
-        let link_preview_service = Arc::new(Mutex::new(LinkPreviewService::new(Arc::clone(
-            &database_pool,
-        ))));
-        let hacker_news_service = Arc::new(Mutex::new(HackerNewsService::new()));
+        let environment = Environment::new();
+        let database_pool = AppData::make_db_pool(&environment).await;
+
+        AppData::with_database_pool(environment, database_pool)
+    }
+
+    pub fn with_database_pool(environment: Environment, database_pool: PgPool) -> Data<Self> {
+        let environment = Arc::new(environment);
+        let database_pool = Arc::new(database_pool);
+        let image_service = Arc::new(ImageService::new(
+            Arc::clone(&database_pool),
+            environment.public_url.as_str(),
+        ));
+        let link_preview_service = Arc::new(LinkPreviewService::new(
+            Arc::clone(&database_pool),
+            Arc::clone(&image_service),
+        ));
+        let reader_service = Arc::new(ReaderService::new(
+            Arc::clone(&database_pool),
+            Arc::clone(&image_service),
+        ));
+        let search_service = Arc::new(SearchService::new(Arc::clone(&database_pool)));
+        let live_updates = Arc::new(LiveUpdates::new());
+        let unavailable_item_policy = UnavailableItemPolicy::from_str(
+            environment.unavailable_items.as_str(),
+        )
+        .expect("Invalid value for UNAVAILABLE_ITEMS, expected \"skip\" or \"placeholder\"");
+        let mut hacker_news_service =
+            HackerNewsService::new(environment.hacker_news_api_url.as_str())
+                .with_unavailable_item_policy(unavailable_item_policy);
+
+        if environment.mirror_enabled {
+            let mirror = Arc::new(Mirror::new(Arc::clone(&database_pool)));
+
+            hacker_news_service = hacker_news_service.with_mirror(mirror);
+        }
+
+        let hacker_news_service = Arc::new(hacker_news_service);
+        let thread_watcher = Arc::new(ThreadWatcher::new(Arc::clone(&hacker_news_service)));
 
         Data::new(AppData {
             hacker_news_service,
+            image_service,
             link_preview_service,
+            live_updates,
+            reader_service,
+            search_service,
+            thread_watcher,
             database_pool,
             environment,
         })
     }
 
-    async fn make_db_pool(environment: Arc<Environment>) -> PgPool {
+    /// URL the server is reachable at, as configured with `PUBLIC_URL`
+    pub fn public_url(&self) -> &str {
+        self.environment.public_url.trim_end_matches('/')
+    }
+
+    /// Builds the background task which mirrors HackerNews into the
+    /// database, `None` unless `MIRROR_ENABLED` is set.
+    pub fn mirror_poller(&self) -> Option<Poller> {
+        let mirror = self.hacker_news_service.mirror()?;
+
+        Some(Poller::new(
+            Arc::clone(&self.hacker_news_service),
+            mirror,
+            Duration::from_secs(self.environment.mirror_poll_interval),
+            self.environment.mirror_backfill_items,
+        ))
+    }
+
+    async fn make_db_pool(environment: &Environment) -> PgPool {
         PgPool::connect(environment.database_url.as_str())
             .await
             .expect("Failed to create database connection pool instance")
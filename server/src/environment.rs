@@ -1,8 +1,27 @@
 use dotenv::dotenv;
 use std::env;
+use std::str::FromStr;
+
+/// HackerNews API served by Firebase, used when `HACKER_NEWS_API_URL` is
+/// not provided.
+const DEFAULT_HACKER_NEWS_API_URL: &str = "https://hacker-news.firebaseio.com/v0";
+
+/// URL the server is reachable at when `PUBLIC_URL` is not provided.
+const DEFAULT_PUBLIC_URL: &str = "http://0.0.0.0:3000";
 
 pub struct Environment {
     pub database_url: String,
+    /// URL the server is reachable at, used to build proxied image URLs
+    pub public_url: String,
+    pub hacker_news_api_url: String,
+    /// Either "skip" or "placeholder", see `UnavailableItemPolicy`
+    pub unavailable_items: String,
+    /// Mirrors HackerNews into the database when `true`, see `Poller`
+    pub mirror_enabled: bool,
+    /// Seconds between polls to the HackerNews API
+    pub mirror_poll_interval: u64,
+    /// Number of most recent items ingested when the mirror falls behind
+    pub mirror_backfill_items: u64,
 }
 
 impl Environment {
@@ -13,10 +32,32 @@ impl Environment {
 
         Environment {
             database_url: Environment::get("DATABASE_URL"),
+            public_url: Environment::get_or("PUBLIC_URL", DEFAULT_PUBLIC_URL),
+            hacker_news_api_url: Environment::get_or(
+                "HACKER_NEWS_API_URL",
+                DEFAULT_HACKER_NEWS_API_URL,
+            ),
+            unavailable_items: Environment::get_or("UNAVAILABLE_ITEMS", "skip"),
+            mirror_enabled: Environment::get_parsed_or("MIRROR_ENABLED", false),
+            mirror_poll_interval: Environment::get_parsed_or("MIRROR_POLL_INTERVAL", 30),
+            mirror_backfill_items: Environment::get_parsed_or("MIRROR_BACKFILL_ITEMS", 1000),
         }
     }
 
     fn get(key: &str) -> String {
         env::var(key).expect(&format!("Failed to read environment variable: {}", key))
     }
+
+    fn get_or(key: &str, default: &str) -> String {
+        env::var(key).unwrap_or_else(|_| default.to_string())
+    }
+
+    fn get_parsed_or<T: FromStr>(key: &str, default: T) -> T {
+        match env::var(key) {
+            Ok(value) => value
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid value for environment variable: {}", key)),
+            Err(_) => default,
+        }
+    }
 }
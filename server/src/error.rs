@@ -1,88 +1,157 @@
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
-use std::fmt::{Display, Formatter};
+use common::hacker_news::ConversionError;
 use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The HackerNews API couldn't be reached, answered with an error status
+    /// or with a payload which couldn't be parsed
+    #[error("The HackerNews API is unavailable")]
+    UpstreamUnavailable(String),
+    /// The HackerNews API didn't answer in time
+    #[error("The HackerNews API took too long to respond")]
+    UpstreamTimeout(String),
+    /// The host of a page or image couldn't be reached, answered with an
+    /// error status or with something which couldn't be read
+    #[error("The host of the URL is unavailable")]
+    ExternalUnavailable(String),
+    /// The host of a page or image didn't answer in time
+    #[error("The host of the URL took too long to respond")]
+    ExternalTimeout(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    WrongItemType(String),
+    #[error("{0}")]
+    BadRequest(String),
+    /// The URL points somewhere the server refuses to fetch, like private
+    /// networks or schemes other than http and https
+    #[error("{0}")]
+    ForbiddenUrl(String),
+    #[error("An error ocurred accessing the database")]
+    Database(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
 
+/// Body of every error response, `code` is meant to be matched by API
+/// consumers while `message` is meant to be read by humans.
 #[derive(Debug, Serialize)]
-pub struct Error {
+struct ErrorResponse {
     status_code: u16,
+    code: &'static str,
     message: String,
     details: Option<String>,
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
-
 impl Error {
-    pub fn new(status_code: StatusCode, message: &str, details: Option<String>) -> Self {
-        let err = Error {
-            status_code: status_code.as_u16(),
-            message: message.to_string(),
-            details,
-        };
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::UpstreamUnavailable(_) => "UPSTREAM_UNAVAILABLE",
+            Error::UpstreamTimeout(_) => "UPSTREAM_TIMEOUT",
+            Error::ExternalUnavailable(_) => "EXTERNAL_UNAVAILABLE",
+            Error::ExternalTimeout(_) => "EXTERNAL_TIMEOUT",
+            Error::NotFound(_) => "NOT_FOUND",
+            Error::WrongItemType(_) => "WRONG_ITEM_TYPE",
+            Error::BadRequest(_) => "BAD_REQUEST",
+            Error::ForbiddenUrl(_) => "FORBIDDEN_URL",
+            Error::Database(_) => "DATABASE_ERROR",
+            Error::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
 
-        sentry::capture_error(&err);
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Error::UpstreamUnavailable(_) | Error::ExternalUnavailable(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+            Error::UpstreamTimeout(_) | Error::ExternalTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            Error::ForbiddenUrl(_) => StatusCode::FORBIDDEN,
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::WrongItemType(_) | Error::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Error::Database(_) | Error::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
 
-        err
+    /// Underlying cause of the error, only exposed to clients on debug builds
+    fn details(&self) -> Option<String> {
+        match self {
+            Error::UpstreamUnavailable(details)
+            | Error::UpstreamTimeout(details)
+            | Error::ExternalUnavailable(details)
+            | Error::ExternalTimeout(details)
+            | Error::Database(details) => Some(details.clone()),
+            _ => None,
+        }
     }
 
-    pub fn as_http_response(&self) -> HttpResponse {
-        if cfg!(debug_assertions) {
-            HttpResponse::build(StatusCode::from_u16(self.status_code).unwrap()).json(Error {
-                status_code: self.status_code,
-                message: self.message.clone(),
-                details: self.details.clone(),
-            })
-        } else {
-            HttpResponse::build(StatusCode::from_u16(self.status_code).unwrap()).json(Error {
-                status_code: self.status_code,
-                message: self.message.clone(),
-                details: None,
-            })
+    /// Error of a failed request to the host of a page or image
+    pub fn external(err: reqwest::Error) -> Self {
+        println!("{:#?}", err);
+
+        if err.is_timeout() {
+            return Error::ExternalTimeout(err.to_string());
         }
+
+        Error::ExternalUnavailable(err.to_string())
     }
-}
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:#?}", self)
+    pub fn as_http_response(&self) -> HttpResponse {
+        let status_code = self.status_code();
+
+        if status_code.is_server_error() {
+            sentry::capture_error(self);
+        }
+
+        let details = if cfg!(debug_assertions) {
+            self.details()
+        } else {
+            None
+        };
+
+        HttpResponse::build(status_code).json(ErrorResponse {
+            status_code: status_code.as_u16(),
+            code: self.code(),
+            message: self.to_string(),
+            details,
+        })
     }
 }
 
-impl std::error::Error for Error {}
-
 impl From<anyhow::Error> for Error {
     fn from(err: anyhow::Error) -> Self {
         println!("{:#?}", err);
 
-        Error::new(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            err.to_string().as_str(),
-            None,
-        )
+        Error::Internal(err.to_string())
     }
 }
 
+/// Requests made with `?` go to the HackerNews API, requests to other hosts
+/// are mapped with `Error::external` instead.
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
         println!("{:#?}", err);
 
-        Error::new(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "An error ocurred fetching the resource",
-            None,
-        )
+        if err.is_timeout() {
+            return Error::UpstreamTimeout(err.to_string());
+        }
+
+        Error::UpstreamUnavailable(err.to_string())
     }
 }
 
+/// JSON parsed with `?` comes out of HackerNews API responses, so a payload
+/// which can't be parsed is an upstream failure. JSON from other hosts, like
+/// oEmbed responses, is mapped to `Error::ExternalUnavailable` instead.
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
         println!("{:#?}", err);
 
-        Error::new(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "An error ocurred fetching the resource",
-            None,
-        )
+        Error::UpstreamUnavailable(err.to_string())
     }
 }
 
@@ -90,10 +159,18 @@ impl From<sqlx::error::Error> for Error {
     fn from(err: sqlx::error::Error) -> Self {
         println!("{:#?}", err);
 
-        Error::new(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "An error ocurred fetching the resource",
-            None,
-        )
+        Error::Database(err.to_string())
+    }
+}
+
+impl From<ConversionError> for Error {
+    fn from(err: ConversionError) -> Self {
+        match err {
+            ConversionError::Deleted(_) | ConversionError::Dead(_) => {
+                Error::NotFound(err.to_string())
+            }
+            ConversionError::WrongType { .. } => Error::WrongItemType(err.to_string()),
+            ConversionError::MissingField { .. } => Error::UpstreamUnavailable(err.to_string()),
+        }
     }
 }
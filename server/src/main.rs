@@ -5,8 +5,12 @@ mod routes;
 mod services;
 mod utils;
 
+#[cfg(test)]
+mod testing;
+
 use actix_web::{dev::Service, http::HeaderValue, App, HttpServer};
 use reqwest::header;
+use std::sync::Arc;
 
 use self::app_data::AppData;
 
@@ -27,6 +31,18 @@ async fn main() -> std::io::Result<()> {
 
     std::env::set_var("RUST_BACKTRACE", "1");
 
+    actix_web::rt::spawn(Arc::clone(&data.live_updates).run(Arc::clone(&data.hacker_news_service)));
+
+    actix_web::rt::spawn(data.link_preview_service.as_ref().clone().run_purge());
+
+    actix_web::rt::spawn(data.reader_service.as_ref().clone().run_purge());
+
+    actix_web::rt::spawn(data.image_service.as_ref().clone().run_purge());
+
+    if let Some(poller) = data.mirror_poller() {
+        actix_web::rt::spawn(poller.run());
+    }
+
     HttpServer::new(move || {
         App::new()
             .app_data(data.clone())
@@ -44,7 +60,7 @@ async fn main() -> std::io::Result<()> {
 
                     headers.insert(
                         header::ACCESS_CONTROL_ALLOW_METHODS,
-                        HeaderValue::from_static("GET,OPTIONS"),
+                        HeaderValue::from_static("GET,POST,OPTIONS"),
                     );
 
                     headers.insert(
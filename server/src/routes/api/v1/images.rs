@@ -0,0 +1,65 @@
+use actix_web::web::{Data, HttpRequest, Path, Query};
+use actix_web::HttpResponse;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::services::images::ThumbnailSize;
+use crate::AppData;
+
+#[derive(Debug, Deserialize)]
+pub struct FindImageParams {
+    #[serde(default)]
+    size: ThumbnailSize,
+}
+
+pub async fn find_one(
+    app_data: Data<AppData>,
+    hash: Path<String>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let params = match Query::<FindImageParams>::from_query(req.query_string()) {
+        Ok(params) => params,
+        Err(err) => {
+            return Error::BadRequest(format!(
+                "Invalid query params, \"size\" must be one of: card, hero or icon: {}",
+                err
+            ))
+            .as_http_response()
+        }
+    };
+
+    match app_data
+        .image_service
+        .find_thumbnail(&hash.into_inner(), params.size)
+        .await
+    {
+        // thumbnails of an image never change, as the hash belongs to the
+        // original URL
+        Ok(thumbnail) => HttpResponse::Ok()
+            .content_type(thumbnail.content_type)
+            .insert_header(("Cache-Control", "public, max-age=31536000, immutable"))
+            .body(thumbnail.data),
+        Err(err) => err.as_http_response(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, TestRequest};
+
+    use crate::testing::app;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn rejects_unknown_thumbnail_sizes() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/images/ABCDEF?size=poster")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
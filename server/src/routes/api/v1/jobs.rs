@@ -0,0 +1,41 @@
+use actix_web::web::{Data, HttpRequest, Path, Query};
+use actix_web::HttpResponse;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::AppData;
+
+#[derive(Debug, Deserialize)]
+pub struct ListJobsParams {
+    cursor: Option<String>,
+    limit: Option<usize>,
+}
+
+pub async fn list_jobs(app_data: Data<AppData>, req: HttpRequest) -> HttpResponse {
+    let params = match Query::<ListJobsParams>::from_query(req.query_string()) {
+        Ok(params) => params,
+        Err(err) => {
+            return Error::BadRequest(format!(
+                "Invalid query params, \"limit\" must be a positive number: {}",
+                err
+            ))
+            .as_http_response()
+        }
+    };
+
+    match app_data
+        .hacker_news_service
+        .find_jobs(params.cursor.as_deref(), params.limit)
+        .await
+    {
+        Ok(jobs) => HttpResponse::Ok().json(jobs),
+        Err(err) => err.as_http_response(),
+    }
+}
+
+pub async fn find_one(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
+    match app_data.hacker_news_service.find_job(&id).await {
+        Ok(job) => HttpResponse::Ok().json(job),
+        Err(err) => err.as_http_response(),
+    }
+}
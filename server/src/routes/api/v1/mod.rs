@@ -1,2 +1,8 @@
+pub mod images;
+pub mod jobs;
+pub mod polls;
 pub mod previews;
+pub mod search;
+pub mod stats;
 pub mod stories;
+pub mod users;
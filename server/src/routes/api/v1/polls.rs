@@ -0,0 +1,35 @@
+use actix_web::web::{Data, Path};
+use actix_web::HttpResponse;
+
+use crate::AppData;
+
+pub async fn find_one(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
+    match app_data.hacker_news_service.find_poll(&id).await {
+        Ok(poll) => HttpResponse::Ok().json(poll),
+        Err(err) => err.as_http_response(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body_json, TestRequest};
+    use serde_json::Value;
+
+    use crate::testing::app;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn rejects_items_of_another_type() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get().uri("/api/v1/polls/8863").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let body: Value = read_body_json(res).await;
+
+        assert_eq!(body["code"], "WRONG_ITEM_TYPE");
+    }
+}
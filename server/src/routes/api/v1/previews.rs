@@ -1,46 +1,111 @@
-use actix_web::http::StatusCode;
-use actix_web::web::{Data, HttpRequest, Query};
+use actix_web::web::{Bytes, Data, HttpRequest, Query};
 use actix_web::HttpResponse;
-use common::LinkPreview;
+use common::LinkPreviewBatch;
 use serde::Deserialize;
 
 use crate::error::Error;
 use crate::AppData;
 
+/// Maximum number of URLs previewed in a single batch
+const MAX_BATCH_SIZE: usize = 100;
+
 #[derive(Debug, Deserialize)]
 pub struct FetchPreviewParams {
     url: Option<String>,
 }
 
 pub async fn fetch_preview(app_data: Data<AppData>, req: HttpRequest) -> HttpResponse {
-    let params = Query::<FetchPreviewParams>::from_query(req.query_string()).unwrap();
+    let params = match Query::<FetchPreviewParams>::from_query(req.query_string()) {
+        Ok(params) => params,
+        Err(err) => {
+            return Error::BadRequest(format!("Invalid query params: {}", err)).as_http_response()
+        }
+    };
 
     if let Some(url) = params.url.clone() {
-        if let Some(preview) = app_data
+        return match app_data
             .link_preview_service
-            .lock()
-            .await
             .preview_from_url(url.as_str())
             .await
         {
-            let title = preview.title;
-            let description = preview.description;
-            let image_url = preview.image_url.and_then(|url| Some(url.to_string()));
-
-            return HttpResponse::Ok().json(LinkPreview {
-                title,
-                description,
-                image_url,
-            });
+            Ok(Some(preview)) => HttpResponse::Ok().json(preview),
+            Ok(None) => HttpResponse::NoContent().finish(),
+            Err(err) => err.as_http_response(),
+        };
+    }
+
+    Error::BadRequest(String::from("The URL query param is required")).as_http_response()
+}
+
+/// Answers a `LinkPreviewBatch` with a map of each URL to its preview, URLs
+/// without a preview are left out.
+pub async fn fetch_previews_batch(app_data: Data<AppData>, body: Bytes) -> HttpResponse {
+    let batch = match serde_json::from_slice::<LinkPreviewBatch>(&body) {
+        Ok(batch) => batch,
+        Err(err) => {
+            return Error::BadRequest(format!("Invalid batch of URLs: {}", err)).as_http_response()
         }
+    };
 
-        return HttpResponse::NoContent().finish();
+    if batch.urls.len() > MAX_BATCH_SIZE {
+        return Error::BadRequest(format!("A batch holds up to {} URLs", MAX_BATCH_SIZE))
+            .as_http_response();
     }
 
-    Error::new(
-        StatusCode::BAD_REQUEST,
-        "The URL query param is required",
-        None,
-    )
-    .as_http_response()
+    let previews = app_data
+        .link_preview_service
+        .previews_from_urls(&batch.urls)
+        .await;
+
+    HttpResponse::Ok().json(previews)
+}
+
+/// Browsers ask for permission before sending JSON bodies to another origin,
+/// CORS headers are added to every response.
+pub async fn allow_batch() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body_json, TestRequest};
+    use common::{LinkPreview, LinkPreviewBatch};
+    use std::collections::HashMap;
+
+    use crate::testing::app;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn answers_empty_batches_of_previews() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::post()
+            .uri("/api/v1/previews/batch")
+            .set_json(&LinkPreviewBatch { urls: Vec::new() })
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let previews: HashMap<String, LinkPreview> = read_body_json(res).await;
+
+        assert!(previews.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn rejects_batches_of_previews_over_the_limit() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let urls = (0..101)
+            .map(|index| format!("https://example.com/{}", index))
+            .collect();
+        let req = TestRequest::post()
+            .uri("/api/v1/previews/batch")
+            .set_json(&LinkPreviewBatch { urls })
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
 }
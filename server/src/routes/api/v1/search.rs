@@ -0,0 +1,72 @@
+use actix_web::web::{Data, HttpRequest, Query};
+use actix_web::HttpResponse;
+use common::hacker_news::Type;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::services::search::{SearchQuery, SearchSort};
+use crate::AppData;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    q: Option<String>,
+    r#type: Option<Type>,
+    by: Option<String>,
+    since: Option<u64>,
+    until: Option<u64>,
+    #[serde(default)]
+    sort: SearchSort,
+    cursor: Option<String>,
+    limit: Option<usize>,
+}
+
+pub async fn search(app_data: Data<AppData>, req: HttpRequest) -> HttpResponse {
+    let params = match Query::<SearchParams>::from_query(req.query_string()) {
+        Ok(params) => params.into_inner(),
+        Err(err) => {
+            return Error::BadRequest(format!(
+                "Invalid query params, \"sort\" must be one of: relevance or date: {}",
+                err
+            ))
+            .as_http_response()
+        }
+    };
+    let query = SearchQuery {
+        text: params.q.unwrap_or_default(),
+        r#type: params.r#type,
+        by: params.by,
+        since: params.since,
+        until: params.until,
+        sort: params.sort,
+    };
+
+    match app_data
+        .search_service
+        .search(&query, params.cursor.as_deref(), params.limit)
+        .await
+    {
+        Ok(hits) => HttpResponse::Ok().json(hits),
+        Err(err) => err.as_http_response(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, TestRequest};
+
+    use crate::testing::app;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn rejects_searches_without_a_query() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/search?type=story")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,50 @@
+use actix_web::web::Data;
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::services::hacker_news::CacheStats;
+use crate::AppData;
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    item_cache: CacheStats,
+}
+
+pub async fn find_stats(app_data: Data<AppData>) -> HttpResponse {
+    HttpResponse::Ok().json(Stats {
+        item_cache: app_data.hacker_news_service.cache_stats(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body_json, TestRequest};
+    use serde_json::Value;
+
+    use crate::testing::app;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn counts_item_cache_hits_and_misses() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+
+        for _ in 0..2 {
+            let req = TestRequest::get().uri("/api/v1/stories/8863").to_request();
+
+            call_service(&app, req).await;
+        }
+
+        let req = TestRequest::get().uri("/api/v1/stats").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body: Value = read_body_json(res).await;
+
+        assert_eq!(body["item_cache"]["hits"], 1);
+        assert_eq!(body["item_cache"]["misses"], 1);
+        assert_eq!(body["item_cache"]["size"], 1);
+    }
+}
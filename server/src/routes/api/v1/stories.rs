@@ -1,12 +1,28 @@
-use actix_web::web::{Data, HttpRequest, Path, Query};
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler};
+use actix_web::rt::time::{interval_at, Instant};
+use actix_web::web::{Bytes, Data, HttpRequest, Path, Payload, Query};
 use actix_web::HttpResponse;
+use actix_web_actors::ws::{self, Message, ProtocolError, WebsocketContext};
+use common::hacker_news::ThreadEvent;
+use futures::stream::{self, StreamExt};
 use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
 
+use crate::error::Error;
+use crate::services::hacker_news::Feed;
 use crate::AppData;
 
+/// Time between comments sent to keep idle live connections open
+const LIVE_KEEP_ALIVE: Duration = Duration::from_secs(20);
+
 #[derive(Debug, Deserialize)]
 pub struct ListStoriesParams {
-    page: Option<usize>,
+    #[serde(default)]
+    feed: Feed,
+    cursor: Option<String>,
+    limit: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -14,26 +30,70 @@ pub struct FindStoryKidsParams {
     id: u64,
 }
 
-pub async fn list_new_stories(app_data: Data<AppData>, req: HttpRequest) -> HttpResponse {
-    let params = Query::<ListStoriesParams>::from_query(req.query_string()).unwrap();
+pub async fn list_stories(app_data: Data<AppData>, req: HttpRequest) -> HttpResponse {
+    let params = match Query::<ListStoriesParams>::from_query(req.query_string()) {
+        Ok(params) => params,
+        Err(err) => {
+            let message = format!(
+                "Invalid query params, \"feed\" must be one of: top, best, new, ask, show or job: {}",
+                err
+            );
+
+            return Error::BadRequest(message).as_http_response();
+        }
+    };
 
     match app_data
         .hacker_news_service
-        .lock()
-        .await
-        .find_new_stories(params.page)
+        .find_stories(params.feed, params.cursor.as_deref(), params.limit)
         .await
     {
-        Ok(story) => HttpResponse::Ok().json(story),
+        Ok(stories) => HttpResponse::Ok().json(stories),
         Err(err) => err.as_http_response(),
     }
 }
 
+/// Streams `LiveEvent`s as Server-Sent Events, every event is sent as a
+/// JSON encoded `message`.
+pub async fn live(app_data: Data<AppData>) -> HttpResponse {
+    let events = stream::unfold(
+        app_data.live_updates.subscribe(),
+        |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    // subscribers which fall behind miss the oldest events
+                    // instead of holding the channel back
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        },
+    )
+    .filter_map(|event| async move {
+        serde_json::to_string(&event)
+            .ok()
+            .map(|json| Bytes::from(format!("data: {}\n\n", json)))
+    });
+    let keep_alive = stream::unfold(
+        interval_at(Instant::now() + LIVE_KEEP_ALIVE, LIVE_KEEP_ALIVE),
+        |mut interval| async move {
+            interval.tick().await;
+            Some((Bytes::from_static(b": keep-alive\n\n"), interval))
+        },
+    );
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(Box::pin(
+            stream::select(events, keep_alive).map(Ok::<Bytes, Error>),
+        ))
+}
+
 pub async fn find_one(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
     match app_data
         .hacker_news_service
-        .lock()
-        .await
         .find_story(&id.into_inner())
         .await
     {
@@ -42,15 +102,299 @@ pub async fn find_one(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
     }
 }
 
-pub async fn find_story_kids(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
-    match app_data
+/// Article of the page the story links to, stories without a URL have no
+/// article.
+pub async fn find_article(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
+    let story = match app_data
         .hacker_news_service
-        .lock()
-        .await
-        .find_story_comments(&id)
+        .find_story(&id.into_inner())
         .await
     {
+        Ok(story) => story,
+        Err(err) => return err.as_http_response(),
+    };
+    let url = match story.url {
+        Some(url) => url,
+        None => {
+            return Error::NotFound(String::from("The story doesn't link to a page"))
+                .as_http_response()
+        }
+    };
+
+    match app_data.reader_service.find_article(&url).await {
+        Ok(article) => HttpResponse::Ok().json(article),
+        Err(err) => err.as_http_response(),
+    }
+}
+
+pub async fn find_story_kids(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
+    match app_data.hacker_news_service.find_story_comments(&id).await {
         Ok(stories) => HttpResponse::Ok().json(stories),
         Err(err) => err.as_http_response(),
     }
 }
+
+pub async fn find_story_thread(app_data: Data<AppData>, id: Path<u64>) -> HttpResponse {
+    match app_data.hacker_news_service.find_story_thread(&id).await {
+        Ok(thread) => HttpResponse::Ok().json(thread),
+        Err(err) => err.as_http_response(),
+    }
+}
+
+/// Upgrades to a WebSocket which pushes `ThreadEvent`s for the story's
+/// thread as JSON text messages.
+pub async fn watch_thread(
+    app_data: Data<AppData>,
+    id: Path<u64>,
+    req: HttpRequest,
+    stream: Payload,
+) -> HttpResponse {
+    let id = id.into_inner();
+
+    // unknown stories are answered before upgrading the connection
+    if let Err(err) = app_data.hacker_news_service.find_story(&id).await {
+        return err.as_http_response();
+    }
+
+    let session = ThreadSession {
+        events: Some(app_data.thread_watcher.watch(id)),
+    };
+
+    match ws::start(session, &req, stream) {
+        Ok(response) => response,
+        Err(err) => Error::BadRequest(err.to_string()).as_http_response(),
+    }
+}
+
+/// Forwards the changes of a story's thread for as long as its WebSocket
+/// is open, the thread is walked by the `ThreadWatcher` which is shared by
+/// every session watching the same story.
+struct ThreadSession {
+    events: Option<Receiver<ThreadEvent>>,
+}
+
+impl Actor for ThreadSession {
+    type Context = WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        if let Some(receiver) = self.events.take() {
+            ctx.add_stream(stream::unfold(receiver, |mut receiver| async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => return Some((event, receiver)),
+                        // sessions which fall behind miss the oldest events
+                        // instead of holding the thread's walks back
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }));
+        }
+    }
+}
+
+impl StreamHandler<ThreadEvent> for ThreadSession {
+    fn handle(&mut self, event: ThreadEvent, ctx: &mut Self::Context) {
+        if let Ok(json) = serde_json::to_string(&event) {
+            ctx.text(json);
+        }
+    }
+}
+
+impl StreamHandler<Result<Message, ProtocolError>> for ThreadSession {
+    fn handle(&mut self, message: Result<Message, ProtocolError>, ctx: &mut Self::Context) {
+        match message {
+            Ok(Message::Ping(bytes)) => ctx.pong(&bytes),
+            Ok(Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(_) => {}
+            Err(_) => ctx.stop(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::dev::MessageBody;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body_json, TestRequest};
+    use common::hacker_news::{CommentNode, LiveEvent, Story};
+    use common::Page;
+    use futures::future::{join_all, poll_fn};
+    use serde_json::Value;
+    use std::pin::Pin;
+    use std::time::{Duration, Instant};
+
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+    use crate::testing::{app, app_with_data, make_app_data};
+
+    #[actix_rt::test]
+    async fn lists_stories_from_a_feed() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/stories?feed=ask")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let stories: Page<Story> = read_body_json(res).await;
+
+        assert_eq!(stories.items.len(), 1);
+        assert_eq!(stories.items[0].title, "Ask HN: The Arc Effect");
+        assert!(!stories.has_more);
+    }
+
+    #[actix_rt::test]
+    async fn serves_concurrent_requests_in_parallel() {
+        // time taken by the fake API to answer every request
+        let latency = Duration::from_millis(300);
+        let api = FakeHackerNewsApi::spawn_with_latency(latency);
+        let app = app(&api).await;
+        let uris = [
+            "/api/v1/stories/8863",
+            "/api/v1/stories/121003",
+            "/api/v1/stories/126809",
+            "/api/v1/jobs/192327",
+        ];
+        let started_at = Instant::now();
+        let responses = join_all(
+            uris.iter()
+                .map(|uri| call_service(&app, TestRequest::get().uri(uri).to_request())),
+        )
+        .await;
+        let elapsed = started_at.elapsed();
+
+        assert!(responses.iter().all(|res| res.status() == StatusCode::OK));
+        // serialized requests would take `uris.len() * latency`
+        assert!(
+            elapsed < latency * 3,
+            "{} requests took {:?}",
+            uris.len(),
+            elapsed
+        );
+    }
+
+    #[actix_rt::test]
+    async fn rejects_unknown_feeds() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/stories?feed=hot")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let body: Value = read_body_json(res).await;
+
+        assert_eq!(body["code"], "BAD_REQUEST");
+    }
+
+    #[actix_rt::test]
+    async fn rejects_limits_out_of_range() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/stories?feed=new&limit=500")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn finds_a_story() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get().uri("/api/v1/stories/8863").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let story: Story = read_body_json(res).await;
+
+        assert_eq!(story.id, 8863);
+    }
+
+    #[actix_rt::test]
+    async fn answers_not_found_for_unknown_items() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get().uri("/api/v1/stories/1").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let body: Value = read_body_json(res).await;
+
+        assert_eq!(body["code"], "NOT_FOUND");
+    }
+
+    #[actix_rt::test]
+    async fn finds_a_story_thread() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/stories/8863/thread")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let thread: Vec<CommentNode> = read_body_json(res).await;
+
+        assert_eq!(thread[0].replies[0].comment.id, 9272);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_thread_watchers_without_a_websocket_upgrade() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/stories/8863/ws")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let body: Value = read_body_json(res).await;
+
+        assert_eq!(body["code"], "BAD_REQUEST");
+    }
+
+    #[actix_rt::test]
+    async fn streams_live_updates() {
+        let api = FakeHackerNewsApi::spawn();
+        let app_data = make_app_data(&api);
+        let app = app_with_data(app_data.clone()).await;
+        let req = TestRequest::get().uri("/api/v1/stories/live").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get("Content-Type").unwrap(),
+            "text/event-stream"
+        );
+
+        app_data.live_updates.publish(LiveEvent::StoryUpdated {
+            id: 8863,
+            score: 111,
+            descendants: Some(71),
+        });
+
+        let mut body = res.into_body();
+        let chunk = poll_fn(|cx| Pin::new(&mut body).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            chunk,
+            "data: {\"kind\":\"story_updated\",\"id\":8863,\"score\":111,\"descendants\":71}\n\n"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use actix_web::web::{Data, HttpRequest, Path, Query};
+use actix_web::HttpResponse;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::AppData;
+
+#[derive(Debug, Deserialize)]
+pub struct FindUserParams {
+    page: Option<usize>,
+}
+
+pub async fn find_one(app_data: Data<AppData>, id: Path<String>, req: HttpRequest) -> HttpResponse {
+    let params = match Query::<FindUserParams>::from_query(req.query_string()) {
+        Ok(params) => params,
+        Err(err) => {
+            return Error::BadRequest(format!(
+                "Invalid query params, \"page\" must be a positive number: {}",
+                err
+            ))
+            .as_http_response()
+        }
+    };
+
+    match app_data
+        .hacker_news_service
+        .find_user_profile(&id, params.page)
+        .await
+    {
+        Ok(profile) => HttpResponse::Ok().json(profile),
+        Err(err) => err.as_http_response(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body_json, TestRequest};
+    use common::hacker_news::UserProfile;
+
+    use crate::testing::app;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn finds_a_user_profile() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/api/v1/users/pg?page=0")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let profile: UserProfile = read_body_json(res).await;
+
+        assert_eq!(profile.user.id, "pg");
+        assert_eq!(profile.items.len(), 3);
+    }
+}
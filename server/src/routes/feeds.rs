@@ -0,0 +1,174 @@
+use actix_web::web::{Data, HttpRequest, Query};
+use actix_web::HttpResponse;
+use common::hacker_news::Story;
+use futures::future::join_all;
+use serde::Deserialize;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::services::feeds::{self, FeedEntry, FeedFilters, FeedFormat};
+use crate::services::hacker_news::Feed;
+use crate::AppData;
+
+/// Stories rendered in a feed unless a `limit` is provided
+const FEED_SIZE: usize = 30;
+
+/// Pages of the feed looked through for stories matching the filters,
+/// feeds with fewer matching stories are rendered with what was found.
+const MAX_FEED_PAGES: usize = 5;
+
+#[derive(Debug, Deserialize)]
+pub struct RenderFeedParams {
+    min_score: Option<u32>,
+    /// Comma separated list of domains stories must link to
+    domains: Option<String>,
+    /// Comma separated list of domains stories must not link to
+    exclude_domains: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Renders a story feed as RSS or Atom, stories are described with their
+/// stored link previews.
+pub async fn render_feed(app_data: Data<AppData>, req: HttpRequest) -> HttpResponse {
+    let params = match Query::<RenderFeedParams>::from_query(req.query_string()) {
+        Ok(params) => params,
+        Err(err) => {
+            return Error::BadRequest(format!("Invalid query params: {}", err)).as_http_response()
+        }
+    };
+    let feed = match Feed::from_str(req.match_info().query("feed")) {
+        Ok(feed) => feed,
+        Err(err) => return err.as_http_response(),
+    };
+    let format = match FeedFormat::from_str(req.match_info().query("format")) {
+        Ok(format) => format,
+        Err(err) => return err.as_http_response(),
+    };
+    let filters = FeedFilters::new(
+        params.min_score,
+        params.domains.as_deref(),
+        params.exclude_domains.as_deref(),
+    );
+    let limit = params.limit.unwrap_or(FEED_SIZE);
+    let stories = match find_matching_stories(&app_data, feed, &filters, limit).await {
+        Ok(stories) => stories,
+        Err(err) => return err.as_http_response(),
+    };
+    let link_preview_service = &app_data.link_preview_service;
+    let entries = join_all(stories.into_iter().map(|story| async move {
+        let preview = match story.url.as_deref() {
+            Some(url) => link_preview_service.find_stored_preview(url).await,
+            None => None,
+        };
+
+        FeedEntry { story, preview }
+    }))
+    .await;
+    let self_url = {
+        let connection_info = req.connection_info();
+
+        format!(
+            "{}://{}{}",
+            connection_info.scheme(),
+            connection_info.host(),
+            req.uri()
+        )
+    };
+
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .body(feeds::render(format, feed, &self_url, &entries))
+}
+
+/// Pages through `feed` until `limit` stories matching `filters` are found,
+/// up to `MAX_FEED_PAGES` pages.
+async fn find_matching_stories(
+    app_data: &AppData,
+    feed: Feed,
+    filters: &FeedFilters,
+    limit: usize,
+) -> Result<Vec<Story>> {
+    let mut stories = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    for _ in 0..MAX_FEED_PAGES {
+        let page = app_data
+            .hacker_news_service
+            .find_stories(feed, cursor.as_deref(), Some(limit))
+            .await?;
+
+        stories.extend(
+            page.items
+                .into_iter()
+                .filter(|story| filters.matches(story)),
+        );
+
+        if stories.len() >= limit {
+            break;
+        }
+
+        match page.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+
+    stories.truncate(limit);
+
+    Ok(stories)
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body, TestRequest};
+
+    use crate::testing::app;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn renders_feeds_as_rss() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get().uri("/feeds/ask.rss").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "application/rss+xml; charset=utf-8"
+        );
+
+        let body = String::from_utf8(read_body(res).await.to_vec()).unwrap();
+
+        assert!(body.contains("<title>Ask HN: The Arc Effect</title>"));
+    }
+
+    #[actix_rt::test]
+    async fn pages_through_feeds_for_stories_matching_the_filters() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        // only the third story of the feed matches
+        let req = TestRequest::get()
+            .uri("/feeds/new.rss?limit=1&min_score=30&exclude_domains=getdropbox.com")
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let body = String::from_utf8(read_body(res).await.to_vec()).unwrap();
+
+        assert!(body.contains("Poll: What would happen if News.YC had explicit support for polls?"));
+        assert!(!body.contains("My YC app: Dropbox"));
+    }
+
+    #[actix_rt::test]
+    async fn rejects_unknown_feed_formats() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get().uri("/feeds/ask.json").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,7 +1,13 @@
 use actix_files::{Files, NamedFile};
-use actix_web::web::{get, scope, ServiceConfig};
+use actix_web::http::Method;
+use actix_web::web::{get, method, post, scope, Data, ServiceConfig};
+use std::fs;
+
+use crate::services::pages::PageTemplate;
 
 mod api;
+mod feeds;
+mod pages;
 
 #[cfg(debug_assertions)]
 const STATIC_SERVE_FROM: &str = "./dist";
@@ -10,25 +16,71 @@ const STATIC_SERVE_FROM: &str = "./dist";
 const STATIC_SERVE_FROM: &str = "./static";
 
 pub fn bind_routes(app: &mut ServiceConfig) {
-    // API
+    bind_api_routes(app);
+    bind_feed_routes(app);
+    bind_page_routes(
+        app,
+        fs::read_to_string(format!("{}/index.html", STATIC_SERVE_FROM)).unwrap(),
+    );
+
+    // In order to handle client-side routing accordingly the `index.html` file
+    // is always served using the `default_handler`.
+    app.service(
+        Files::new("/", STATIC_SERVE_FROM)
+            .default_handler(NamedFile::open(format!("{}/index.html", STATIC_SERVE_FROM)).unwrap())
+            .index_file("index.html"),
+    );
+}
+
+pub fn bind_api_routes(app: &mut ServiceConfig) {
     app.service(
         scope("/api").service(
             scope("/v1")
                 .service(
                     scope("/stories")
-                        .route("", get().to(api::v1::stories::list_new_stories))
+                        .route("", get().to(api::v1::stories::list_stories))
+                        .route("/live", get().to(api::v1::stories::live))
                         .route("/{id}", get().to(api::v1::stories::find_one))
-                        .route("/{id}/kids", get().to(api::v1::stories::find_story_kids)),
+                        .route("/{id}/kids", get().to(api::v1::stories::find_story_kids))
+                        .route("/{id}/article", get().to(api::v1::stories::find_article))
+                        .route(
+                            "/{id}/thread",
+                            get().to(api::v1::stories::find_story_thread),
+                        )
+                        .route("/{id}/ws", get().to(api::v1::stories::watch_thread)),
+                )
+                .service(
+                    scope("/jobs")
+                        .route("", get().to(api::v1::jobs::list_jobs))
+                        .route("/{id}", get().to(api::v1::jobs::find_one)),
+                )
+                .service(scope("/images").route("/{hash}", get().to(api::v1::images::find_one)))
+                .service(scope("/polls").route("/{id}", get().to(api::v1::polls::find_one)))
+                .service(scope("/users").route("/{id}", get().to(api::v1::users::find_one)))
+                .service(
+                    scope("/previews")
+                        .route("", get().to(api::v1::previews::fetch_preview))
+                        .route("/batch", post().to(api::v1::previews::fetch_previews_batch))
+                        .route(
+                            "/batch",
+                            method(Method::OPTIONS).to(api::v1::previews::allow_batch),
+                        ),
                 )
-                .service(scope("/previews").route("", get().to(api::v1::previews::fetch_preview))),
+                .service(scope("/search").route("", get().to(api::v1::search::search)))
+                .route("/stats", get().to(api::v1::stats::find_stats)),
         ),
     );
+}
 
-    // In order to handle client-side routing accordingly the `index.html` file
-    // is always served using the `default_handler`.
-    app.service(
-        Files::new("/", STATIC_SERVE_FROM)
-            .default_handler(NamedFile::open(format!("{}/index.html", STATIC_SERVE_FROM)).unwrap())
-            .index_file("index.html"),
-    );
+/// Pages rendered by the server, these are served from the `index.html` of
+/// the client bundle which is rendered into.
+pub fn bind_page_routes(app: &mut ServiceConfig, index_html: String) {
+    app.app_data(Data::new(PageTemplate::new(index_html)))
+        .route("/", get().to(pages::render_home))
+        .route("/stories/{id}", get().to(pages::render_story));
+}
+
+/// RSS and Atom renditions of the story feeds, such as `/feeds/top.rss`
+pub fn bind_feed_routes(app: &mut ServiceConfig) {
+    app.service(scope("/feeds").route("/{feed}.{format}", get().to(feeds::render_feed)));
 }
@@ -0,0 +1,144 @@
+use actix_web::rt::time::timeout;
+use actix_web::web::{Data, HttpRequest};
+use actix_web::HttpResponse;
+use common::LinkPreview;
+use std::time::Duration;
+
+use crate::services::hacker_news::Feed;
+use crate::services::pages::PageTemplate;
+use crate::AppData;
+
+/// Time the preview of a story's page is waited for when it isn't stored
+/// yet, link unfurlers don't wait long for the page.
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Renders the stream of new stories, as the client does on `/`.
+pub async fn render_home(app_data: Data<AppData>, template: Data<PageTemplate>) -> HttpResponse {
+    match app_data
+        .hacker_news_service
+        .find_stories(Feed::New, None, None)
+        .await
+    {
+        Ok(page) => html(template.render_stream("new", page)),
+        Err(err) => {
+            eprintln!("An error ocurred rendering the home page:\n{:?}", err);
+            html(template.fallback().to_string())
+        }
+    }
+}
+
+/// Renders the page of a story along with the tags links to it unfurl
+/// with. Stories which can't be found or fetched are answered with the
+/// bundle and a 404, so these aren't indexed, and left to the client to
+/// report.
+pub async fn render_story(
+    app_data: Data<AppData>,
+    template: Data<PageTemplate>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let id = match req.match_info().query("id").parse::<u64>() {
+        Ok(id) => id,
+        Err(_) => return not_found(&template),
+    };
+    let story = match app_data.hacker_news_service.find_story(&id).await {
+        Ok(story) => story,
+        Err(err) => {
+            eprintln!("An error ocurred rendering the story page:\n{:?}", err);
+            return not_found(&template);
+        }
+    };
+    let link_preview = match story.url.as_deref() {
+        Some(url) => find_preview(&app_data, url).await,
+        None => None,
+    };
+    // the Host header is up to the client, so links are built from the
+    // URL the server is configured to be reachable at
+    let page_url = format!("{}/stories/{}", app_data.public_url(), id);
+
+    html(template.render_story(story, link_preview, &page_url))
+}
+
+/// Stored preview of `url`, the page is fetched for a while if there is
+/// none as unfurled links are usually the first request for a story.
+async fn find_preview(app_data: &AppData, url: &str) -> Option<LinkPreview> {
+    if let Some(link_preview) = app_data.link_preview_service.find_stored_preview(url).await {
+        return Some(link_preview);
+    }
+
+    match timeout(
+        PREVIEW_TIMEOUT,
+        app_data.link_preview_service.preview_from_url(url),
+    )
+    .await
+    {
+        Ok(Ok(link_preview)) => link_preview,
+        Ok(Err(err)) => {
+            eprintln!("An error ocurred previewing the story:\n{:?}", err);
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+fn html(body: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(body)
+}
+
+fn not_found(template: &PageTemplate) -> HttpResponse {
+    HttpResponse::NotFound()
+        .content_type("text/html; charset=utf-8")
+        .body(template.fallback().to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body, TestRequest};
+
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+    use crate::testing::{app, INDEX_HTML};
+
+    #[actix_rt::test]
+    async fn renders_story_pages_on_the_server() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get()
+            .uri("/stories/8863")
+            .insert_header(("Host", "fluxcap.example"))
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "text/html; charset=utf-8"
+        );
+
+        let body = String::from_utf8(read_body(res).await.to_vec()).unwrap();
+
+        assert!(body.contains(
+            r#"<h2 class="story-title">My YC app: Dropbox - Throw away your USB drive</h2>"#
+        ));
+        assert!(body.contains(r#"<script id="initial-data" type="application/json">"#));
+        assert!(body.contains(
+            r#"<meta property="og:title" content="My YC app: Dropbox - Throw away your USB drive" />"#
+        ));
+        assert!(body
+            .contains("<title>My YC app: Dropbox - Throw away your USB drive | FluxCap</title>"));
+        assert!(body
+            .contains(r#"<meta property="og:url" content="http://0.0.0.0:3000/stories/8863" />"#));
+    }
+
+    #[actix_rt::test]
+    async fn answers_unknown_stories_with_the_bundle_and_not_found() {
+        let api = FakeHackerNewsApi::spawn();
+        let app = app(&api).await;
+        let req = TestRequest::get().uri("/stories/1").to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(read_body(res).await, INDEX_HTML.as_bytes());
+    }
+}
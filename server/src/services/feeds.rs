@@ -0,0 +1,385 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use common::hacker_news::Story;
+use common::html::escape;
+use common::LinkPreview;
+use reqwest::Url;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::services::hacker_news::Feed;
+
+/// Discussion page of an item on HackerNews
+const HACKER_NEWS_ITEM_URL: &str = "https://news.ycombinator.com/item?id=";
+
+/// Formats story feeds are rendered to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+impl FeedFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+}
+
+impl FromStr for FeedFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "rss" => Ok(FeedFormat::Rss),
+            "atom" => Ok(FeedFormat::Atom),
+            _ => Err(Error::BadRequest(format!(
+                "Unknown feed format \"{}\", expected one of: rss or atom",
+                s
+            ))),
+        }
+    }
+}
+
+/// Stories left out of a rendered feed. Domains match their subdomains as
+/// well, stories without a URL never match a domain.
+#[derive(Debug, Default)]
+pub struct FeedFilters {
+    pub min_score: Option<u32>,
+    pub domains: Vec<String>,
+    pub exclude_domains: Vec<String>,
+}
+
+impl FeedFilters {
+    /// Builds filters from comma separated lists of domains
+    pub fn new(
+        min_score: Option<u32>,
+        domains: Option<&str>,
+        exclude_domains: Option<&str>,
+    ) -> Self {
+        FeedFilters {
+            min_score,
+            domains: FeedFilters::parse_domains(domains),
+            exclude_domains: FeedFilters::parse_domains(exclude_domains),
+        }
+    }
+
+    pub fn matches(&self, story: &Story) -> bool {
+        if let Some(min_score) = self.min_score {
+            if story.score < min_score {
+                return false;
+            }
+        }
+
+        let domain = story.url.as_deref().and_then(domain_of);
+        let matches_any = |domains: &[String]| match domain.as_deref() {
+            Some(domain) => domains.iter().any(|allowed| {
+                domain == allowed.as_str() || domain.ends_with(&format!(".{}", allowed))
+            }),
+            None => false,
+        };
+
+        if !self.domains.is_empty() && !matches_any(&self.domains) {
+            return false;
+        }
+
+        !matches_any(&self.exclude_domains)
+    }
+
+    fn parse_domains(domains: Option<&str>) -> Vec<String> {
+        domains
+            .unwrap_or_default()
+            .split(',')
+            .map(|domain| domain.trim().trim_start_matches("www.").to_lowercase())
+            .filter(|domain| !domain.is_empty())
+            .collect()
+    }
+}
+
+/// A story along with the stored link preview of its URL, if any
+pub struct FeedEntry {
+    pub story: Story,
+    pub preview: Option<LinkPreview>,
+}
+
+/// Renders the entries of a feed, `self_url` is the URL the document is
+/// served from.
+pub fn render(format: FeedFormat, feed: Feed, self_url: &str, entries: &[FeedEntry]) -> String {
+    match format {
+        FeedFormat::Rss => render_rss(feed, self_url, entries),
+        FeedFormat::Atom => render_atom(feed, self_url, entries),
+    }
+}
+
+fn render_rss(feed: Feed, self_url: &str, entries: &[FeedEntry]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+
+    xml.push_str(
+        r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/elements/1.1/">"#,
+    );
+    xml.push_str("<channel>");
+    xml.push_str(&format!("<title>{}</title>", escape(&title(feed))));
+    xml.push_str(&format!("<link>{}</link>", escape(self_url)));
+    xml.push_str(&format!(
+        r#"<atom:link href="{}" rel="self" type="application/rss+xml"/>"#,
+        escape(self_url)
+    ));
+    xml.push_str(&format!(
+        "<description>{}</description>",
+        escape(&title(feed))
+    ));
+    xml.push_str(&format!(
+        "<lastBuildDate>{}</lastBuildDate>",
+        updated_at(entries).to_rfc2822()
+    ));
+
+    for FeedEntry { story, preview } in entries {
+        let discussion_url = format!("{}{}", HACKER_NEWS_ITEM_URL, story.id);
+
+        xml.push_str("<item>");
+        xml.push_str(&format!("<title>{}</title>", escape(&story.title)));
+        xml.push_str(&format!(
+            "<link>{}</link>",
+            escape(story.url.as_deref().unwrap_or(&discussion_url))
+        ));
+        xml.push_str(&format!(
+            r#"<guid isPermaLink="true">{}</guid>"#,
+            escape(&discussion_url)
+        ));
+        xml.push_str(&format!("<comments>{}</comments>", escape(&discussion_url)));
+        xml.push_str(&format!("<dc:creator>{}</dc:creator>", escape(&story.by)));
+        xml.push_str(&format!(
+            "<pubDate>{}</pubDate>",
+            datetime(story.time).to_rfc2822()
+        ));
+
+        if let Some(preview) = preview {
+            if let Some(description) = preview.description.as_deref() {
+                xml.push_str(&format!(
+                    "<description>{}</description>",
+                    escape(description)
+                ));
+            }
+
+            if let Some(image_url) = preview.image_url.as_ref() {
+                // the size of the image is unknown without fetching it
+                xml.push_str(&format!(
+                    r#"<enclosure url="{}" length="0" type="{}"/>"#,
+                    escape(image_url.as_str()),
+                    image_type(image_url)
+                ));
+            }
+        }
+
+        xml.push_str("</item>");
+    }
+
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+fn render_atom(feed: Feed, self_url: &str, entries: &[FeedEntry]) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    xml.push_str(&format!("<title>{}</title>", escape(&title(feed))));
+    xml.push_str(&format!("<id>{}</id>", escape(self_url)));
+    xml.push_str(&format!(
+        r#"<link rel="self" href="{}"/>"#,
+        escape(self_url)
+    ));
+    xml.push_str(&format!(
+        "<updated>{}</updated>",
+        updated_at(entries).to_rfc3339()
+    ));
+
+    for FeedEntry { story, preview } in entries {
+        let discussion_url = format!("{}{}", HACKER_NEWS_ITEM_URL, story.id);
+
+        xml.push_str("<entry>");
+        xml.push_str(&format!("<title>{}</title>", escape(&story.title)));
+        xml.push_str(&format!("<id>{}</id>", escape(&discussion_url)));
+        xml.push_str(&format!(
+            r#"<link href="{}"/>"#,
+            escape(story.url.as_deref().unwrap_or(&discussion_url))
+        ));
+        xml.push_str(&format!(
+            r#"<link rel="replies" type="text/html" href="{}"/>"#,
+            escape(&discussion_url)
+        ));
+        xml.push_str(&format!(
+            "<author><name>{}</name></author>",
+            escape(&story.by)
+        ));
+        xml.push_str(&format!(
+            "<updated>{}</updated>",
+            datetime(story.time).to_rfc3339()
+        ));
+
+        if let Some(preview) = preview {
+            if let Some(description) = preview.description.as_deref() {
+                xml.push_str(&format!("<summary>{}</summary>", escape(description)));
+            }
+
+            if let Some(image_url) = preview.image_url.as_ref() {
+                xml.push_str(&format!(
+                    r#"<link rel="enclosure" type="{}" href="{}"/>"#,
+                    image_type(image_url),
+                    escape(image_url.as_str())
+                ));
+            }
+        }
+
+        xml.push_str("</entry>");
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
+fn title(feed: Feed) -> String {
+    let name = match feed {
+        Feed::Top => "Top stories",
+        Feed::Best => "Best stories",
+        Feed::New => "New stories",
+        Feed::Ask => "Ask HN",
+        Feed::Show => "Show HN",
+        Feed::Job => "Jobs",
+    };
+
+    format!("fluxcap: {}", name)
+}
+
+/// Feeds are as recent as their most recent story
+fn updated_at(entries: &[FeedEntry]) -> DateTime<Utc> {
+    entries
+        .iter()
+        .map(|entry| entry.story.time)
+        .max()
+        .map(datetime)
+        .unwrap_or_else(Utc::now)
+}
+
+fn datetime(time: u64) -> DateTime<Utc> {
+    DateTime::from_utc(NaiveDateTime::from_timestamp(time as i64, 0), Utc)
+}
+
+fn domain_of(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+
+    Some(host.trim_start_matches("www.").to_lowercase())
+}
+
+/// Guesses the MIME type of an image from the extension in its URL
+fn image_type(url: &str) -> &'static str {
+    let path = match Url::parse(url) {
+        Ok(url) => url.path().to_lowercase(),
+        Err(_) => return "image/jpeg",
+    };
+
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".gif") {
+        "image/gif"
+    } else if path.ends_with(".webp") {
+        "image/webp"
+    } else if path.ends_with(".svg") {
+        "image/svg+xml"
+    } else {
+        "image/jpeg"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::hacker_news::{Story, Type};
+    use common::LinkPreview;
+
+    use crate::services::hacker_news::Feed;
+
+    use super::{render, FeedEntry, FeedFilters, FeedFormat};
+
+    fn story(id: u64, score: u32, url: Option<&str>) -> Story {
+        Story {
+            id,
+            r#type: Type::Story,
+            by: String::from("dhouston"),
+            time: 1175714200,
+            kids: None,
+            url: url.map(String::from),
+            score,
+            title: String::from("My YC app: Dropbox & co"),
+            descendants: Some(71),
+        }
+    }
+
+    fn entry() -> FeedEntry {
+        FeedEntry {
+            story: story(
+                8863,
+                111,
+                Some("http://www.getdropbox.com/u/2/screencast.html"),
+            ),
+            preview: Some(LinkPreview {
+                title: Some(String::from("Dropbox")),
+                description: Some(String::from("Throw away your USB drive")),
+                domain: Some(String::from("getdropbox.com")),
+                image_url: Some(String::from("https://getdropbox.com/logo.png")),
+                ..LinkPreview::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn renders_rss_items_with_enclosures() {
+        let xml = render(
+            FeedFormat::Rss,
+            Feed::Top,
+            "https://fluxcap.herokuapp.com/feeds/top.rss",
+            &[entry()],
+        );
+
+        assert!(xml.contains("<title>fluxcap: Top stories</title>"));
+        assert!(xml.contains("<title>My YC app: Dropbox &amp; co</title>"));
+        assert!(xml.contains("<link>http://www.getdropbox.com/u/2/screencast.html</link>"));
+        assert!(xml.contains("<description>Throw away your USB drive</description>"));
+        assert!(xml.contains(
+            r#"<enclosure url="https://getdropbox.com/logo.png" length="0" type="image/png"/>"#
+        ));
+        assert!(xml.contains("<pubDate>Wed, 04 Apr 2007 19:16:40 +0000</pubDate>"));
+    }
+
+    #[test]
+    fn renders_atom_entries_with_enclosures() {
+        let xml = render(
+            FeedFormat::Atom,
+            Feed::Top,
+            "https://fluxcap.herokuapp.com/feeds/top.atom",
+            &[entry()],
+        );
+
+        assert!(xml.contains("<id>https://news.ycombinator.com/item?id=8863</id>"));
+        assert!(xml.contains("<updated>2007-04-04T19:16:40+00:00</updated>"));
+        assert!(xml.contains("<summary>Throw away your USB drive</summary>"));
+        assert!(xml.contains(
+            r#"<link rel="enclosure" type="image/png" href="https://getdropbox.com/logo.png"/>"#
+        ));
+    }
+
+    #[test]
+    fn filters_stories_by_score_and_domain() {
+        let filters = FeedFilters::new(Some(100), Some("github.com, rust-lang.org"), None);
+
+        assert!(filters.matches(&story(1, 120, Some("https://blog.rust-lang.org/"))));
+        assert!(!filters.matches(&story(2, 80, Some("https://github.com/"))));
+        assert!(!filters.matches(&story(3, 120, Some("https://example.com/"))));
+        assert!(!filters.matches(&story(4, 120, None)));
+
+        let filters = FeedFilters::new(None, None, Some("medium.com"));
+
+        assert!(filters.matches(&story(5, 1, None)));
+        assert!(!filters.matches(&story(6, 1, Some("https://www.medium.com/@pg"))));
+    }
+}
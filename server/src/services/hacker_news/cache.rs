@@ -0,0 +1,303 @@
+use common::hacker_news::{Item, Type};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Stories (and polls and jobs) keep receiving votes and comments while
+/// they are fresh, so they are kept for a short period of time.
+const STORY_TTL: Duration = Duration::from_secs(60);
+
+/// Comments could be edited or receive new replies while they are fresh.
+const COMMENT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Dead, deleted and old items are not expected to change anymore.
+const SETTLED_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Age in seconds after which an item is considered settled.
+const SETTLED_AGE: u64 = 2 * 24 * 60 * 60;
+
+struct Entry {
+    item: Item,
+    expires_at: Instant,
+}
+
+/// Entries along with their IDs ordered by expiration, so expired entries
+/// and the ones closer to expire are found without a scan.
+struct Entries {
+    items: HashMap<u64, Entry>,
+    expirations: BTreeSet<(Instant, u64)>,
+}
+
+impl Entries {
+    fn with_capacity(capacity: usize) -> Self {
+        Entries {
+            items: HashMap::with_capacity(capacity),
+            expirations: BTreeSet::new(),
+        }
+    }
+
+    fn insert(&mut self, item: Item, expires_at: Instant) {
+        let id = item.id;
+
+        if let Some(previous) = self.items.insert(id, Entry { item, expires_at }) {
+            self.expirations.remove(&(previous.expires_at, id));
+        }
+
+        self.expirations.insert((expires_at, id));
+    }
+
+    fn remove(&mut self, id: &u64) {
+        if let Some(entry) = self.items.remove(id) {
+            self.expirations.remove(&(entry.expires_at, *id));
+        }
+    }
+
+    /// Removes every entry which expired by `now`.
+    fn remove_expired(&mut self, now: Instant) {
+        while let Some(&(expires_at, id)) = self.expirations.iter().next() {
+            if expires_at > now {
+                break;
+            }
+
+            self.remove(&id);
+        }
+    }
+
+    /// Removes the entry which is closer to expire.
+    fn remove_first(&mut self) {
+        if let Some(&(_, id)) = self.expirations.iter().next() {
+            self.remove(&id);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+        self.expirations.clear();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub size: usize,
+}
+
+/// Size-bounded in-memory cache of HackerNews items with a time to live
+/// based on the item type and age.
+pub struct ItemCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ItemCache {
+    pub fn new(capacity: usize) -> Self {
+        ItemCache {
+            capacity,
+            entries: Mutex::new(Entries::with_capacity(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, id: &u64) -> Option<Item> {
+        let mut entries = self.entries.lock().unwrap();
+
+        if let Some(entry) = entries.items.get(id) {
+            if entry.expires_at > Instant::now() {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Some(entry.item.clone());
+            }
+
+            entries.remove(id);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    pub fn insert(&self, item: Item) {
+        let expires_at = Instant::now() + ItemCache::ttl(&item);
+
+        self.insert_until(item, expires_at);
+    }
+
+    pub fn invalidate(&self, id: &u64) {
+        self.entries.lock().unwrap().remove(id);
+    }
+
+    #[allow(dead_code)]
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            size: self.entries.lock().unwrap().items.len(),
+        }
+    }
+
+    fn insert_until(&self, item: Item, expires_at: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+
+        if entries.items.len() >= self.capacity && !entries.items.contains_key(&item.id) {
+            entries.remove_expired(Instant::now());
+
+            if entries.items.len() >= self.capacity {
+                // no expired entries were found, make room by evicting the
+                // entry which is closer to expire
+                entries.remove_first();
+            }
+        }
+
+        entries.insert(item, expires_at);
+    }
+
+    /// Time during which `item` is served without checking upstream, the
+    /// mirror uses the same to tell whether its copy is fresh.
+    pub fn ttl(item: &Item) -> Duration {
+        if item.deleted.unwrap_or(false) || item.dead.unwrap_or(false) {
+            return SETTLED_TTL;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        if now.saturating_sub(item.time) > SETTLED_AGE {
+            return SETTLED_TTL;
+        }
+
+        match item.r#type {
+            Type::Comment => COMMENT_TTL,
+            Type::Story | Type::Job | Type::Poll | Type::PollOpt => STORY_TTL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::hacker_news::{Item, Type};
+    use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+    use super::{CacheStats, ItemCache, COMMENT_TTL, SETTLED_TTL, STORY_TTL};
+
+    fn make_item(id: u64, r#type: Type) -> Item {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Item {
+            id,
+            deleted: None,
+            r#type,
+            by: Some(String::from("pg")),
+            time,
+            text: None,
+            dead: None,
+            parent: None,
+            poll: None,
+            kids: None,
+            url: None,
+            score: Some(1),
+            title: Some(String::from("Hello World")),
+            parts: None,
+            descendants: None,
+        }
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let cache = ItemCache::new(10);
+
+        assert_eq!(cache.get(&1), None);
+
+        cache.insert(make_item(1, Type::Story));
+
+        assert_eq!(cache.get(&1), Some(make_item(1, Type::Story)));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                size: 1
+            }
+        );
+    }
+
+    #[test]
+    fn invalidates_entries() {
+        let cache = ItemCache::new(10);
+
+        cache.insert(make_item(1, Type::Story));
+        cache.invalidate(&1);
+
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn drops_expired_entries() {
+        let cache = ItemCache::new(10);
+
+        cache.insert_until(make_item(1, Type::Story), Instant::now());
+
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.stats().size, 0);
+    }
+
+    #[test]
+    fn evicts_entries_when_full() {
+        let cache = ItemCache::new(2);
+
+        cache.insert(make_item(1, Type::Story));
+        cache.insert(make_item(2, Type::Comment));
+        cache.insert(make_item(3, Type::Comment));
+
+        assert_eq!(cache.stats().size, 2);
+        // the story has the shortest time to live so its evicted first
+        assert_eq!(cache.get(&1), None);
+        assert!(cache.get(&2).is_some());
+        assert!(cache.get(&3).is_some());
+    }
+
+    #[test]
+    fn evicts_expired_entries_first() {
+        let cache = ItemCache::new(2);
+
+        cache.insert_until(make_item(1, Type::Comment), Instant::now());
+        cache.insert(make_item(2, Type::Story));
+        cache.insert(make_item(3, Type::Comment));
+
+        assert_eq!(cache.stats().size, 2);
+        assert!(cache.get(&2).is_some());
+        assert!(cache.get(&3).is_some());
+    }
+
+    #[test]
+    fn uses_ttl_based_on_item_type_and_state() {
+        let story = make_item(1, Type::Story);
+        let comment = make_item(2, Type::Comment);
+        let mut dead_comment = make_item(3, Type::Comment);
+        let mut old_story = make_item(4, Type::Story);
+
+        dead_comment.dead = Some(true);
+        old_story.time = 0;
+
+        assert_eq!(ItemCache::ttl(&story), STORY_TTL);
+        assert_eq!(ItemCache::ttl(&comment), COMMENT_TTL);
+        assert_eq!(ItemCache::ttl(&dead_comment), SETTLED_TTL);
+        assert_eq!(ItemCache::ttl(&old_story), SETTLED_TTL);
+    }
+}
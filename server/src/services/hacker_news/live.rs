@@ -0,0 +1,182 @@
+use actix_web::rt::time::interval;
+use common::hacker_news::{LiveEvent, Story};
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+use crate::error::Result;
+
+use super::{Feed, HackerNewsService, PAGE_SIZE, THREAD_MAX_CONCURRENCY};
+
+/// Time between polls to `/updates.json` and `/newstories.json`.
+const LIVE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Events kept for subscribers which fall behind, older events are dropped.
+const LIVE_CHANNEL_CAPACITY: usize = 256;
+
+/// The score and comment count last published for each updated story.
+type StoryCounts = HashMap<u64, (u32, Option<u64>)>;
+
+/// Broadcasts new stories and story changes to every subscriber of the live
+/// stories stream.
+pub struct LiveUpdates {
+    sender: Sender<LiveEvent>,
+}
+
+impl LiveUpdates {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(LIVE_CHANNEL_CAPACITY);
+
+        LiveUpdates { sender }
+    }
+
+    pub fn subscribe(&self) -> Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, event: LiveEvent) {
+        // sending only fails when there are no subscribers, which is fine
+        let _ = self.sender.send(event);
+    }
+
+    /// Polls upstream for as long as the server runs, upstream is left alone
+    /// while nobody is subscribed.
+    pub async fn run(self: Arc<Self>, service: Arc<HackerNewsService>) {
+        let mut interval = interval(LIVE_POLL_INTERVAL);
+        let mut known_ids: Option<HashSet<u64>> = None;
+        let mut counts = StoryCounts::new();
+
+        loop {
+            interval.tick().await;
+
+            if self.sender.receiver_count() == 0 {
+                known_ids = None;
+                counts.clear();
+                continue;
+            }
+
+            if let Err(err) = self.poll(&service, &mut known_ids, &mut counts).await {
+                eprintln!("An error ocurred polling live updates:\n{:?}", err);
+            }
+        }
+    }
+
+    async fn poll(
+        &self,
+        service: &HackerNewsService,
+        known_ids: &mut Option<HashSet<u64>>,
+        counts: &mut StoryCounts,
+    ) -> Result<()> {
+        let new_ids = service.fetch_json::<Vec<u64>>(Feed::New.path()).await?;
+        let new_ids: Vec<u64> = new_ids.into_iter().take(PAGE_SIZE).collect();
+
+        // the first poll only learns which stories are there already
+        if let Some(known) = known_ids.as_ref() {
+            let arrived: Vec<u64> = new_ids
+                .iter()
+                .rev()
+                .filter(|id| !known.contains(id))
+                .copied()
+                .collect();
+
+            for id in arrived {
+                if let Ok(story) = service.find_story(&id).await {
+                    self.publish(LiveEvent::NewStory { story });
+                }
+            }
+        }
+
+        *known_ids = Some(new_ids.into_iter().collect());
+
+        let updates = service.find_updates().await?;
+        let stories = stream::iter(updates.items.iter())
+            .map(|id| service.refresh_item(id))
+            .buffer_unordered(THREAD_MAX_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .filter_map(|item| item.ok().flatten())
+            .filter_map(|item| Story::try_from(item).ok());
+
+        // stories stay in `/updates.json` for a while, so only the ones whose
+        // score or comments changed since the last poll are published
+        let mut updated_counts = StoryCounts::new();
+
+        for story in stories {
+            let story_counts = (story.score, story.descendants);
+
+            if counts.get(&story.id) != Some(&story_counts) {
+                self.publish(LiveEvent::StoryUpdated {
+                    id: story.id,
+                    score: story.score,
+                    descendants: story.descendants,
+                });
+            }
+
+            updated_counts.insert(story.id, story_counts);
+        }
+
+        *counts = updated_counts;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::hacker_news::LiveEvent;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    use super::{LiveUpdates, StoryCounts};
+    use crate::services::hacker_news::HackerNewsService;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    #[actix_rt::test]
+    async fn broadcasts_events_to_every_subscriber() {
+        let live_updates = LiveUpdates::new();
+        let mut first = live_updates.subscribe();
+        let mut second = live_updates.subscribe();
+        let event = LiveEvent::StoryUpdated {
+            id: 8863,
+            score: 111,
+            descendants: Some(71),
+        };
+
+        live_updates.publish(event.clone());
+
+        assert_eq!(first.recv().await.unwrap(), event);
+        assert_eq!(second.recv().await.unwrap(), event);
+    }
+
+    #[actix_rt::test]
+    async fn publishes_story_updates_only_when_they_change() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let live_updates = LiveUpdates::new();
+        let mut receiver = live_updates.subscribe();
+        let mut known_ids = None;
+        let mut counts = StoryCounts::new();
+
+        live_updates
+            .poll(&service, &mut known_ids, &mut counts)
+            .await
+            .unwrap();
+
+        let mut updated = Vec::new();
+        while let Ok(LiveEvent::StoryUpdated { id, .. }) = receiver.try_recv() {
+            updated.push(id);
+        }
+        updated.sort_unstable();
+        assert_eq!(updated, vec![8863, 126809]);
+
+        live_updates
+            .poll(&service, &mut known_ids, &mut counts)
+            .await
+            .unwrap();
+
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    }
+}
@@ -0,0 +1,304 @@
+use chrono::{DateTime, Utc};
+use common::hacker_news::{Item, Type, User};
+use sqlx::{query, query_as, FromRow, PgPool};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+
+use super::cache::ItemCache;
+
+/// Local copy of HackerNews items and users stored in the `items` and
+/// `users` tables, kept up to date by the `Poller`.
+pub struct Mirror {
+    database_pool: Arc<PgPool>,
+}
+
+/// An item as stored in the mirror, along with the last time it was
+/// stored.
+#[derive(Debug)]
+pub struct MirroredItem {
+    pub item: Item,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MirroredItem {
+    /// Items which were stored within the same time to live used by the
+    /// item cache are fresh, the rest are fetched from upstream again.
+    pub fn is_fresh(&self) -> bool {
+        let age = (Utc::now() - self.updated_at).to_std().unwrap_or_default();
+
+        age < ItemCache::ttl(&self.item)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct ItemsRow {
+    id: i64,
+    #[sqlx(rename = "type")]
+    item_type: String,
+    author: Option<String>,
+    time: i64,
+    text: Option<String>,
+    deleted: bool,
+    dead: bool,
+    parent: Option<i64>,
+    poll: Option<i64>,
+    kids: Option<Vec<i64>>,
+    url: Option<String>,
+    score: Option<i32>,
+    title: Option<String>,
+    parts: Option<Vec<i64>>,
+    descendants: Option<i64>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct UsersRow {
+    id: String,
+    created: i64,
+    karma: i64,
+    about: Option<String>,
+    submitted: Option<Vec<i64>>,
+}
+
+impl ItemsRow {
+    fn into_mirrored_item(self) -> Result<MirroredItem> {
+        let item = Item {
+            id: self.id as u64,
+            deleted: Some(self.deleted).filter(|deleted| *deleted),
+            r#type: Mirror::parse_type(&self.item_type)?,
+            by: self.author,
+            time: self.time as u64,
+            text: self.text,
+            dead: Some(self.dead).filter(|dead| *dead),
+            parent: self.parent.map(|id| id as u64),
+            poll: self.poll.map(|id| id as u64),
+            kids: self.kids.map(Mirror::to_ids),
+            url: self.url,
+            score: self.score.map(|score| score as u32),
+            title: self.title,
+            parts: self.parts.map(Mirror::to_ids),
+            descendants: self.descendants.map(|descendants| descendants as u64),
+        };
+
+        Ok(MirroredItem {
+            item,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl From<UsersRow> for User {
+    fn from(row: UsersRow) -> Self {
+        User {
+            id: row.id,
+            created: row.created as u64,
+            karma: row.karma as u64,
+            about: row.about,
+            submitted: row.submitted.map(Mirror::to_ids),
+        }
+    }
+}
+
+impl Mirror {
+    pub fn new(database_pool: Arc<PgPool>) -> Self {
+        Mirror { database_pool }
+    }
+
+    pub async fn find_item(&self, id: &u64) -> Result<Option<MirroredItem>> {
+        let row: Option<ItemsRow> = query_as("SELECT * FROM items WHERE id = $1")
+            .bind(*id as i64)
+            .fetch_optional(&*self.database_pool)
+            .await?;
+
+        row.map(ItemsRow::into_mirrored_item).transpose()
+    }
+
+    pub async fn find_user(&self, id: &str) -> Result<Option<User>> {
+        let row: Option<UsersRow> = query_as("SELECT * FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&*self.database_pool)
+            .await?;
+
+        Ok(row.map(User::from))
+    }
+
+    pub async fn store_item(&self, item: &Item) -> Result<()> {
+        query(
+            r#"
+        INSERT INTO items (
+            id,
+            type,
+            author,
+            time,
+            text,
+            deleted,
+            dead,
+            parent,
+            poll,
+            kids,
+            url,
+            score,
+            title,
+            parts,
+            descendants
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
+        ) ON CONFLICT (id) DO UPDATE SET
+            type = EXCLUDED.type,
+            author = EXCLUDED.author,
+            time = EXCLUDED.time,
+            text = EXCLUDED.text,
+            deleted = EXCLUDED.deleted,
+            dead = EXCLUDED.dead,
+            parent = EXCLUDED.parent,
+            poll = EXCLUDED.poll,
+            kids = EXCLUDED.kids,
+            url = EXCLUDED.url,
+            score = EXCLUDED.score,
+            title = EXCLUDED.title,
+            parts = EXCLUDED.parts,
+            descendants = EXCLUDED.descendants,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(item.id as i64)
+        .bind(Mirror::type_name(&item.r#type))
+        .bind(item.by.clone())
+        .bind(item.time as i64)
+        .bind(item.text.clone())
+        .bind(item.deleted.unwrap_or(false))
+        .bind(item.dead.unwrap_or(false))
+        .bind(item.parent.map(|id| id as i64))
+        .bind(item.poll.map(|id| id as i64))
+        .bind(item.kids.as_deref().map(Mirror::from_ids))
+        .bind(item.url.clone())
+        .bind(item.score.map(|score| score as i32))
+        .bind(item.title.clone())
+        .bind(item.parts.as_deref().map(Mirror::from_ids))
+        .bind(item.descendants.map(|descendants| descendants as i64))
+        .execute(&*self.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn store_user(&self, user: &User) -> Result<()> {
+        query(
+            r#"
+        INSERT INTO users (
+            id,
+            created,
+            karma,
+            about,
+            submitted
+        ) VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5
+        ) ON CONFLICT (id) DO UPDATE SET
+            created = EXCLUDED.created,
+            karma = EXCLUDED.karma,
+            about = EXCLUDED.about,
+            submitted = EXCLUDED.submitted,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(user.id.clone())
+        .bind(user.created as i64)
+        .bind(user.karma as i64)
+        .bind(user.about.clone())
+        .bind(user.submitted.as_deref().map(Mirror::from_ids))
+        .execute(&*self.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Item types are stored with the same names used by the HackerNews API
+    pub fn type_name(r#type: &Type) -> &'static str {
+        match r#type {
+            Type::Job => "job",
+            Type::Story => "story",
+            Type::Comment => "comment",
+            Type::Poll => "poll",
+            Type::PollOpt => "pollopt",
+        }
+    }
+
+    pub fn parse_type(name: &str) -> Result<Type> {
+        match name {
+            "job" => Ok(Type::Job),
+            "story" => Ok(Type::Story),
+            "comment" => Ok(Type::Comment),
+            "poll" => Ok(Type::Poll),
+            "pollopt" => Ok(Type::PollOpt),
+            _ => Err(Error::Database(format!("Unknown item type: {}", name))),
+        }
+    }
+
+    /// Postgres has no unsigned integers, IDs are stored as `BIGINT`
+    fn from_ids(ids: &[u64]) -> Vec<i64> {
+        ids.iter().map(|id| *id as i64).collect()
+    }
+
+    fn to_ids(ids: Vec<i64>) -> Vec<u64> {
+        ids.into_iter().map(|id| id as u64).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+    use common::hacker_news::{Item, Type};
+
+    use super::{Mirror, MirroredItem};
+
+    #[test]
+    fn tells_fresh_items_apart_from_stale_ones() {
+        let story = Item {
+            time: Utc::now().timestamp() as u64,
+            ..Item::unavailable(8863, Type::Story)
+        };
+        let fresh = MirroredItem {
+            item: story.clone(),
+            updated_at: Utc::now(),
+        };
+        let stale = MirroredItem {
+            item: story,
+            updated_at: Utc::now() - Duration::minutes(5),
+        };
+        // old items are settled, so these stay fresh for longer
+        let settled = MirroredItem {
+            item: Item::unavailable(1, Type::Story),
+            updated_at: Utc::now() - Duration::minutes(5),
+        };
+
+        assert!(fresh.is_fresh());
+        assert!(!stale.is_fresh());
+        assert!(settled.is_fresh());
+    }
+
+    #[test]
+    fn stores_types_with_their_api_names() {
+        for r#type in [
+            Type::Job,
+            Type::Story,
+            Type::Comment,
+            Type::Poll,
+            Type::PollOpt,
+        ]
+        .iter()
+        {
+            let name = Mirror::type_name(r#type);
+
+            assert_eq!(
+                serde_json::to_value(r#type).unwrap(),
+                serde_json::Value::from(name)
+            );
+            assert_eq!(&Mirror::parse_type(name).unwrap(), r#type);
+        }
+    }
+}
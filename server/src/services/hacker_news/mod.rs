@@ -0,0 +1,1020 @@
+//! https://github.com/HackerNews/API
+
+mod cache;
+mod live;
+mod mirror;
+mod poller;
+mod snapshot;
+mod thread;
+mod watch;
+
+use common::hacker_news::{
+    Comment, CommentNode, ConversionError, Item, Job, Poll, PollOption, Story, Type, User,
+    UserProfile,
+};
+use common::Page;
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+use self::cache::ItemCache;
+use self::snapshot::{Cursor, SnapshotStore};
+
+pub use self::cache::CacheStats;
+pub use self::live::LiveUpdates;
+pub use self::mirror::Mirror;
+pub use self::poller::Poller;
+pub use self::thread::diff_threads;
+pub use self::watch::ThreadWatcher;
+
+const PAGE_SIZE: usize = 20;
+
+/// Maximum number of items a client is allowed to request in a single page.
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Levels of replies fetched when walking a story's thread, replies nested
+/// deeper than this are left out but their IDs remain available in `kids`.
+const THREAD_MAX_DEPTH: usize = 12;
+
+/// Maximum number of in-flight item requests while walking a thread.
+const THREAD_MAX_CONCURRENCY: usize = 16;
+
+/// Maximum number of items held in memory by the item cache.
+const ITEM_CACHE_CAPACITY: usize = 10_000;
+
+/// Time given to the HackerNews API to answer a request before giving up.
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HackerNews story lists, each one of them is served from its own
+/// endpoint and contains up to 500 item IDs (200 for Ask, Show and Job).
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Feed {
+    Top,
+    Best,
+    New,
+    Ask,
+    Show,
+    Job,
+}
+
+impl Feed {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Feed::Top => "/topstories.json",
+            Feed::Best => "/beststories.json",
+            Feed::New => "/newstories.json",
+            Feed::Ask => "/askstories.json",
+            Feed::Show => "/showstories.json",
+            Feed::Job => "/jobstories.json",
+        }
+    }
+}
+
+impl Default for Feed {
+    fn default() -> Self {
+        Feed::New
+    }
+}
+
+impl FromStr for Feed {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "top" => Ok(Feed::Top),
+            "best" => Ok(Feed::Best),
+            "new" => Ok(Feed::New),
+            "ask" => Ok(Feed::Ask),
+            "show" => Ok(Feed::Show),
+            "job" => Ok(Feed::Job),
+            _ => Err(Error::BadRequest(format!(
+                "Unknown feed \"{}\", expected one of: top, best, new, ask, show or job",
+                s
+            ))),
+        }
+    }
+}
+
+/// What to do with items which can't be listed in a feed, such as deleted
+/// or dead stories, or items which failed to be fetched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnavailableItemPolicy {
+    /// Leave the item out of the feed
+    Skip,
+    /// Keep the item's position in the feed with a placeholder
+    Placeholder,
+}
+
+impl FromStr for UnavailableItemPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "skip" => Ok(UnavailableItemPolicy::Skip),
+            "placeholder" => Ok(UnavailableItemPolicy::Placeholder),
+            _ => Err(Error::Internal(format!(
+                "Unknown unavailable item policy: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Items and profiles changed recently, as served by `/updates.json`.
+#[derive(Debug, Deserialize)]
+pub struct Updates {
+    pub items: Vec<u64>,
+    pub profiles: Vec<String>,
+}
+
+/// Every field holds its own synchronization primitives (if any), so a
+/// single instance is meant to be shared across handlers through an `Arc`.
+pub struct HackerNewsService {
+    base_url: String,
+    client: Client,
+    cache: ItemCache,
+    snapshots: SnapshotStore,
+    mirror: Option<Arc<Mirror>>,
+    unavailable_item_policy: UnavailableItemPolicy,
+}
+
+impl HackerNewsService {
+    pub fn new(base_url: &str) -> Self {
+        HackerNewsService {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Client::builder()
+                .timeout(UPSTREAM_TIMEOUT)
+                .build()
+                .expect("Failed to build the HackerNews API client"),
+            cache: ItemCache::new(ITEM_CACHE_CAPACITY),
+            snapshots: SnapshotStore::new(),
+            mirror: None,
+            unavailable_item_policy: UnavailableItemPolicy::Skip,
+        }
+    }
+
+    pub fn with_unavailable_item_policy(mut self, policy: UnavailableItemPolicy) -> Self {
+        self.unavailable_item_policy = policy;
+        self
+    }
+
+    /// Answers from the local mirror first, items and users missing from
+    /// the mirror are fetched from upstream and stored in it.
+    pub fn with_mirror(mut self, mirror: Arc<Mirror>) -> Self {
+        self.mirror = Some(mirror);
+        self
+    }
+
+    pub fn mirror(&self) -> Option<Arc<Mirror>> {
+        self.mirror.clone()
+    }
+
+    /// Removes an item from the item cache, the next request for this item
+    /// will be served from upstream.
+    pub fn invalidate_item(&self, id: &u64) {
+        self.cache.invalidate(id);
+    }
+
+    /// Hits, misses and size of the item cache, served on `/api/v1/stats`.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Finds a page of stories out of `feed`, the first page is served when
+    /// no `cursor` is provided.
+    pub async fn find_stories(
+        &self,
+        feed: Feed,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Page<Story>> {
+        let (feed_ids, next_cursor) = self.find_feed_ids(feed, cursor, limit).await?;
+        let find_story_futures = feed_ids.iter().map(|id| self.find_feed_item(id));
+        let stories = join_all(find_story_futures)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<Option<Story>>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        Ok(Page {
+            items: stories,
+            has_more: next_cursor.is_some(),
+            next_cursor,
+        })
+    }
+
+    /// Polls are stories with a list of options, so they are found as
+    /// stories as well.
+    pub async fn find_story(&self, id: &u64) -> Result<Story> {
+        let item = self.find_item(id).await?;
+
+        if matches!(item.r#type, Type::Story | Type::Poll) {
+            return Story::try_from(item).map_err(Error::from);
+        }
+
+        if matches!(item.r#type, Type::Job) {
+            return Err(Error::WrongItemType(String::from(
+                "The provided ID belongs to a job item, find it on the jobs endpoint",
+            )));
+        }
+
+        Err(Error::WrongItemType(String::from(
+            "The provided ID doesn't belong to a story item",
+        )))
+    }
+
+    /// Feeds are not limited to stories, `jobstories` is made of job items
+    /// and polls could show up in any of the other feeds. Every one of these
+    /// items carry the fields required to be listed as a `Story`.
+    ///
+    /// Items which can't be listed are handled based on the
+    /// `UnavailableItemPolicy` instead of failing the whole feed.
+    async fn find_feed_item(&self, id: &u64) -> Result<Option<Story>> {
+        let item = match self.find_item(id).await {
+            Ok(item) => item,
+            Err(err) => {
+                return Ok(self
+                    .unavailable_item(id, Type::Story, err)
+                    .map(|item| Story::placeholder(&item)))
+            }
+        };
+
+        match Story::try_from(item.clone()) {
+            Ok(story) => Ok(Some(story)),
+            Err(ConversionError::WrongType { .. }) => Ok(None),
+            Err(_) => match self.unavailable_item_policy {
+                UnavailableItemPolicy::Skip => Ok(None),
+                UnavailableItemPolicy::Placeholder => Ok(Some(Story::placeholder(&item))),
+            },
+        }
+    }
+
+    pub async fn find_jobs(&self, cursor: Option<&str>, limit: Option<usize>) -> Result<Page<Job>> {
+        let (jobstories_ids, next_cursor) = self.find_feed_ids(Feed::Job, cursor, limit).await?;
+        let find_job_futures = jobstories_ids.iter().map(|id| self.find_item(id));
+
+        // job postings have nothing to show once they are gone or can't be
+        // fetched, so these are always skipped
+        let jobs = join_all(find_job_futures)
+            .await
+            .into_iter()
+            .zip(jobstories_ids.iter())
+            .filter_map(|(item, id)| match item {
+                Ok(item) => Job::try_from(item).ok(),
+                Err(err) => {
+                    eprintln!("An error ocurred finding the job {}:\n{:?}", id, err);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Page {
+            items: jobs,
+            has_more: next_cursor.is_some(),
+            next_cursor,
+        })
+    }
+
+    pub async fn find_job(&self, id: &u64) -> Result<Job> {
+        let item = self.find_item(id).await?;
+
+        if matches!(item.r#type, Type::Job) {
+            return Job::try_from(item).map_err(Error::from);
+        }
+
+        Err(Error::WrongItemType(String::from(
+            "The provided ID doesn't belong to a job item",
+        )))
+    }
+
+    /// Finds a poll along with every one of its options
+    pub async fn find_poll(&self, id: &u64) -> Result<Poll> {
+        let item = self.find_item(id).await?;
+
+        if !matches!(item.r#type, Type::Poll) {
+            return Err(Error::WrongItemType(String::from(
+                "The provided ID doesn't belong to a poll item",
+            )));
+        }
+
+        let mut poll = Poll::try_from(item).map_err(Error::from)?;
+        let find_option_futures = poll
+            .parts
+            .iter()
+            .map(|id| self.find_poll_option(id, poll.id));
+
+        poll.options = join_all(find_option_futures)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<Option<PollOption>>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        Ok(poll)
+    }
+
+    /// Options which can't be found are handled based on the
+    /// `UnavailableItemPolicy` instead of failing the whole poll.
+    async fn find_poll_option(&self, id: &u64, poll: u64) -> Result<Option<PollOption>> {
+        let item = match self.find_item(id).await {
+            Ok(item) => item,
+            Err(err) => {
+                return Ok(self
+                    .unavailable_item(id, Type::PollOpt, err)
+                    .map(|item| PollOption::placeholder(&item, poll)))
+            }
+        };
+
+        match PollOption::try_from(item.clone()) {
+            Ok(option) => Ok(Some(option)),
+            Err(err) => Ok(self
+                .unavailable_item(id, Type::PollOpt, Error::from(err))
+                .map(|_| PollOption::placeholder(&item, poll))),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn find_comment(&self, id: &u64) -> Result<Comment> {
+        let item = self.find_item(id).await?;
+
+        if matches!(item.r#type, Type::Comment) {
+            return Comment::try_from(item).map_err(Error::from);
+        }
+
+        Err(Error::WrongItemType(String::from(
+            "The provided ID doesn't belong to a comment item",
+        )))
+    }
+
+    /// Comments in a thread are kept even if these are deleted or dead, so
+    /// their replies remain reachable. Comments which fail to be fetched are
+    /// handled based on the `UnavailableItemPolicy`.
+    async fn find_thread_comment(&self, id: &u64, refresh: bool) -> Result<Option<Comment>> {
+        let item = if refresh {
+            self.refresh_item(id).await.and_then(|item| {
+                item.ok_or_else(|| Error::NotFound(format!("There's no item with ID: {}", id)))
+            })
+        } else {
+            self.find_item(id).await
+        };
+        let item = match item {
+            Ok(item) => item,
+            Err(err) => {
+                return Ok(self
+                    .unavailable_item(id, Type::Comment, err)
+                    .map(|item| Comment::placeholder(&item)))
+            }
+        };
+
+        match Comment::try_from(item.clone()) {
+            Ok(comment) => Ok(Some(comment)),
+            Err(ConversionError::Deleted(_)) | Err(ConversionError::Dead(_)) => {
+                Ok(Some(Comment::placeholder(&item)))
+            }
+            Err(err) => Err(Error::from(err)),
+        }
+    }
+
+    /// Stands for a listed item which couldn't be fetched, either because
+    /// upstream failed or answered with `null`, `None` if it's skipped.
+    fn unavailable_item(&self, id: &u64, r#type: Type, err: Error) -> Option<Item> {
+        eprintln!("An error ocurred finding the item {}:\n{:?}", id, err);
+
+        match self.unavailable_item_policy {
+            UnavailableItemPolicy::Skip => None,
+            UnavailableItemPolicy::Placeholder => Some(Item::unavailable(*id, r#type)),
+        }
+    }
+
+    pub async fn find_story_comments(&self, id: &u64) -> Result<Vec<Comment>> {
+        let story = self.find_story(id).await?;
+
+        if let Some(kids) = story.kids {
+            if kids.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let comments_futures = kids.iter().map(|id| self.find_thread_comment(id, false));
+            let comments = join_all(comments_futures)
+                .await
+                .into_iter()
+                .collect::<Result<Vec<Option<Comment>>>>()?;
+
+            return Ok(comments.into_iter().flatten().collect());
+        }
+
+        Ok(Vec::new())
+    }
+
+    pub async fn find_story_thread(&self, id: &u64) -> Result<Vec<CommentNode>> {
+        self.walk_thread(id, false).await
+    }
+
+    /// Walks a story's thread straight from upstream, bypassing the cache
+    /// and the mirror, and replaces the copies these hold.
+    pub async fn refresh_story_thread(&self, id: &u64) -> Result<Vec<CommentNode>> {
+        self.walk_thread(id, true).await
+    }
+
+    /// Walks the whole `kids` graph of a story, one level of replies at a
+    /// time, and builds the nested comments tree.
+    async fn walk_thread(&self, id: &u64, refresh: bool) -> Result<Vec<CommentNode>> {
+        if refresh {
+            self.refresh_item(id).await?;
+        }
+
+        let story = self.find_story(id).await?;
+        let root_ids = story.kids.unwrap_or_default();
+        let mut comments: HashMap<u64, Comment> = HashMap::new();
+        let mut level = root_ids.clone();
+        let mut depth = 0;
+
+        while !level.is_empty() && depth < THREAD_MAX_DEPTH {
+            let fetched = stream::iter(level.iter())
+                .map(|id| self.find_thread_comment(id, refresh))
+                .buffered(THREAD_MAX_CONCURRENCY)
+                .collect::<Vec<Result<Option<Comment>>>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<Option<Comment>>>>()?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<Comment>>();
+
+            level = fetched
+                .iter()
+                .filter_map(|comment| comment.kids.clone())
+                .flatten()
+                .collect();
+            comments.extend(fetched.into_iter().map(|comment| (comment.id, comment)));
+            depth += 1;
+        }
+
+        Ok(HackerNewsService::build_thread(&root_ids, &mut comments))
+    }
+
+    fn build_thread(ids: &[u64], comments: &mut HashMap<u64, Comment>) -> Vec<CommentNode> {
+        let mut nodes = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            if let Some(comment) = comments.remove(id) {
+                let replies = match comment.kids.as_ref() {
+                    Some(kids) => HackerNewsService::build_thread(kids, comments),
+                    None => Vec::new(),
+                };
+
+                nodes.push(CommentNode { comment, replies });
+            }
+        }
+
+        nodes
+    }
+
+    pub async fn find_user(&self, id: &str) -> Result<User> {
+        if let Some(mirror) = &self.mirror {
+            match mirror.find_user(id).await {
+                Ok(Some(user)) => return Ok(user),
+                Ok(None) => {}
+                Err(err) => eprintln!("An error ocurred reading the mirror:\n{:?}", err),
+            }
+        }
+
+        match self.fetch_user(id).await? {
+            Some(user) => {
+                if let Some(mirror) = &self.mirror {
+                    if let Err(err) = mirror.store_user(&user).await {
+                        eprintln!("An error ocurred storing the user:\n{:?}", err);
+                    }
+                }
+
+                Ok(user)
+            }
+            None => Err(Error::NotFound(format!("There's no user with ID: {}", id))),
+        }
+    }
+
+    /// Finds a user along with a page of its submitted items, `page` starts
+    /// from 0.
+    pub async fn find_user_profile(&self, id: &str, page: Option<usize>) -> Result<UserProfile> {
+        let user = self.find_user(id).await?;
+        let page = page.unwrap_or(0);
+        let submitted = user.submitted.clone().unwrap_or_default();
+        let offset = page * PAGE_SIZE;
+        let page_ids: Vec<u64> = submitted
+            .iter()
+            .skip(offset)
+            .take(PAGE_SIZE)
+            .copied()
+            .collect();
+        let find_item_futures = page_ids.iter().map(|id| self.find_item(id));
+
+        // submissions can be of any type so there's nothing to build a
+        // placeholder from, these are skipped when they can't be fetched
+        let items = join_all(find_item_futures)
+            .await
+            .into_iter()
+            .zip(page_ids.iter())
+            .filter_map(|(item, id)| match item {
+                Ok(item) => Some(item),
+                Err(err) => {
+                    eprintln!("An error ocurred finding the item {}:\n{:?}", id, err);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(UserProfile {
+            user,
+            items,
+            page,
+            has_more: submitted.len() > offset + PAGE_SIZE,
+        })
+    }
+
+    async fn find_max_item_id(&self) -> Result<u64> {
+        self.fetch_json::<u64>("/maxitem.json").await
+    }
+
+    async fn find_updates(&self) -> Result<Updates> {
+        self.fetch_json::<Updates>("/updates.json").await
+    }
+
+    /// Takes a page of IDs out of a snapshot of `feed` along with the cursor
+    /// to the following page, if any.
+    ///
+    /// Without a `cursor` the most recent snapshot of the feed is used, a new
+    /// one is taken from upstream when it is not fresh anymore.
+    async fn find_feed_ids(
+        &self,
+        feed: Feed,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<(Vec<u64>, Option<String>)> {
+        let limit = limit.unwrap_or(PAGE_SIZE);
+
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(Error::BadRequest(format!(
+                "The limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+
+        let (snapshot, offset) = match cursor {
+            Some(cursor) => {
+                let cursor = Cursor::from_str(cursor)?;
+                let snapshot = self.snapshots.get(cursor.version).ok_or_else(|| {
+                    Error::BadRequest(String::from(
+                        "The cursor has expired, start over from the first page",
+                    ))
+                })?;
+
+                if snapshot.feed != feed {
+                    return Err(Error::BadRequest(String::from(
+                        "The cursor belongs to another feed",
+                    )));
+                }
+
+                (snapshot, cursor.offset)
+            }
+            None => match self.snapshots.latest(feed) {
+                Some(snapshot) => (snapshot, 0),
+                None => {
+                    let ids = self.fetch_json::<Vec<u64>>(feed.path()).await?;
+
+                    (self.snapshots.insert(feed, ids), 0)
+                }
+            },
+        };
+        let ids = snapshot
+            .ids
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .copied()
+            .collect();
+        let next_cursor = if offset + limit < snapshot.ids.len() {
+            let cursor = Cursor {
+                version: snapshot.version,
+                offset: offset + limit,
+            };
+
+            Some(cursor.to_string())
+        } else {
+            None
+        };
+
+        Ok((ids, next_cursor))
+    }
+
+    /// Items in the mirror are served while fresh, stale ones are fetched
+    /// from upstream again and only served if upstream can't be reached.
+    async fn find_item(&self, id: &u64) -> Result<Item> {
+        if let Some(item) = self.cache.get(id) {
+            return Ok(item);
+        }
+
+        let mut stale = None;
+
+        if let Some(mirror) = &self.mirror {
+            match mirror.find_item(id).await {
+                Ok(Some(mirrored)) if mirrored.is_fresh() => {
+                    self.cache.insert(mirrored.item.clone());
+
+                    return Ok(mirrored.item);
+                }
+                Ok(Some(mirrored)) => stale = Some(mirrored.item),
+                Ok(None) => {}
+                Err(err) => eprintln!("An error ocurred reading the mirror:\n{:?}", err),
+            }
+        }
+
+        let fetched = match self.fetch_item(id).await {
+            Ok(fetched) => fetched,
+            Err(err) => return stale.ok_or(err),
+        };
+
+        match fetched {
+            Some(item) => {
+                if let Some(mirror) = &self.mirror {
+                    if let Err(err) = mirror.store_item(&item).await {
+                        eprintln!("An error ocurred storing the item:\n{:?}", err);
+                    }
+                }
+
+                self.cache.insert(item.clone());
+
+                Ok(item)
+            }
+            None => Err(Error::NotFound(format!("There's no item with ID: {}", id))),
+        }
+    }
+
+    /// Fetches an item from upstream and replaces any copy of it held by
+    /// the cache and the mirror.
+    async fn refresh_item(&self, id: &u64) -> Result<Option<Item>> {
+        let item = self.fetch_item(id).await?;
+
+        if let Some(item) = item.as_ref() {
+            if let Some(mirror) = &self.mirror {
+                mirror.store_item(item).await?;
+            }
+
+            self.cache.insert(item.clone());
+        }
+
+        Ok(item)
+    }
+
+    /// Fetches an item from upstream, the API answers with `null` for IDs
+    /// which are not assigned yet.
+    async fn fetch_item(&self, id: &u64) -> Result<Option<Item>> {
+        self.fetch_json::<Option<Item>>(&format!("/item/{}.json", id))
+            .await
+    }
+
+    /// Fetches a user from upstream, the API answers with `null` for unknown
+    /// users.
+    async fn fetch_user(&self, id: &str) -> Result<Option<User>> {
+        self.fetch_json::<Option<User>>(&format!("/user/{}.json", id))
+            .await
+    }
+
+    /// Requests `path` from the HackerNews API and parses the JSON response,
+    /// error statuses are treated as the API being unavailable.
+    async fn fetch_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let res = self
+            .client
+            .get(self.uri(path))
+            .send()
+            .await?
+            .error_for_status()?;
+        let text = res.text().await?;
+
+        serde_json::from_str::<T>(&text).map_err(Error::from)
+    }
+
+    fn uri(&self, path: &str) -> String {
+        let mut url = self.base_url.clone();
+
+        url.push_str(path);
+        url
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpListener;
+
+    use crate::error::Error;
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    use super::{Feed, HackerNewsService, UnavailableItemPolicy};
+
+    #[actix_rt::test]
+    async fn finds_new_stories() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let stories = service.find_stories(Feed::New, None, None).await.unwrap();
+        let ids: Vec<u64> = stories.items.into_iter().map(|story| story.id).collect();
+
+        assert_eq!(ids, vec![8863, 121003, 126809]);
+        assert_eq!(stories.next_cursor, None);
+        assert!(!stories.has_more);
+    }
+
+    #[actix_rt::test]
+    async fn pages_through_a_feed_with_a_cursor() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let first = service
+            .find_stories(Feed::New, None, Some(2))
+            .await
+            .unwrap();
+        let ids: Vec<u64> = first.items.iter().map(|story| story.id).collect();
+
+        assert_eq!(ids, vec![8863, 121003]);
+        assert!(first.has_more);
+
+        let second = service
+            .find_stories(Feed::New, first.next_cursor.as_deref(), Some(2))
+            .await
+            .unwrap();
+        let ids: Vec<u64> = second.items.iter().map(|story| story.id).collect();
+
+        assert_eq!(ids, vec![126809]);
+        assert!(!second.has_more);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_cursors_of_another_feed() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let page = service
+            .find_stories(Feed::New, None, Some(1))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            service
+                .find_stories(Feed::Top, page.next_cursor.as_deref(), Some(1))
+                .await,
+            Err(Error::BadRequest(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn keeps_placeholders_for_unavailable_stories() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url())
+            .with_unavailable_item_policy(UnavailableItemPolicy::Placeholder);
+        let stories = service.find_stories(Feed::Top, None, None).await.unwrap();
+        let titles: Vec<String> = stories.items.into_iter().map(|story| story.title).collect();
+
+        assert_eq!(
+            titles,
+            vec![
+                "My YC app: Dropbox - Throw away your USB drive",
+                "[dead]",
+                "Ask HN: The Arc Effect"
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn skips_unavailable_stories() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let stories = service.find_stories(Feed::Top, None, None).await.unwrap();
+        let ids: Vec<u64> = stories.items.into_iter().map(|story| story.id).collect();
+
+        assert_eq!(ids, vec![8863, 121003]);
+        assert!(service.find_story(&27925600).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn skips_items_which_fail_to_be_fetched() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        // item 1 is answered with `null`
+        let stories = service.find_stories(Feed::Best, None, None).await.unwrap();
+        let ids: Vec<u64> = stories.items.into_iter().map(|story| story.id).collect();
+
+        assert_eq!(ids, vec![8863, 121003]);
+    }
+
+    #[actix_rt::test]
+    async fn keeps_placeholders_for_items_which_fail_to_be_fetched() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url())
+            .with_unavailable_item_policy(UnavailableItemPolicy::Placeholder);
+        let stories = service.find_stories(Feed::Best, None, None).await.unwrap();
+        let titles: Vec<String> = stories.items.into_iter().map(|story| story.title).collect();
+
+        assert_eq!(
+            titles,
+            vec![
+                "My YC app: Dropbox - Throw away your USB drive",
+                "[unavailable]",
+                "Ask HN: The Arc Effect"
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn finds_a_story() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let story = service.find_story(&8863).await.unwrap();
+
+        assert_eq!(story.by, "dhouston");
+        assert_eq!(
+            story.title,
+            "My YC app: Dropbox - Throw away your USB drive"
+        );
+        assert_eq!(story.kids, Some(vec![9224, 8917]));
+    }
+
+    #[actix_rt::test]
+    async fn finds_a_comment() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let comment = service.find_comment(&9224).await.unwrap();
+
+        assert_eq!(comment.by, Some(String::from("BrandonM")));
+        assert_eq!(comment.parent, Some(8863));
+    }
+
+    #[actix_rt::test]
+    async fn rejects_items_of_another_type() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+
+        assert!(service.find_story(&9224).await.is_err());
+        assert!(service.find_comment(&8863).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn finds_a_story_thread() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let thread = service.find_story_thread(&8863).await.unwrap();
+
+        assert_eq!(thread.len(), 2);
+        assert_eq!(thread[0].comment.id, 9224);
+        assert_eq!(thread[0].replies[0].comment.id, 9272);
+        // deleted comments are kept along with their replies
+        assert_eq!(
+            thread[1].replies[0].comment.text,
+            Some(String::from("[deleted]"))
+        );
+        assert_eq!(thread[1].replies[0].replies[0].comment.id, 9301);
+    }
+
+    #[actix_rt::test]
+    async fn refreshes_a_story_thread() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let thread = service.refresh_story_thread(&8863).await.unwrap();
+
+        assert_eq!(service.cache_stats().misses, 0);
+        // the refreshed items replace the cached ones
+        assert_eq!(service.find_story_thread(&8863).await.unwrap(), thread);
+        assert_eq!(service.cache_stats().misses, 0);
+    }
+
+    #[actix_rt::test]
+    async fn finds_jobs() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let jobs = service.find_jobs(None, None).await.unwrap().items;
+
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(
+            jobs[0].title,
+            "Justin.tv is looking for a Lead Flash Engineer!"
+        );
+        assert_eq!(jobs[0].url, None);
+        assert!(service.find_story(&192327).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn finds_a_poll_with_its_options() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let poll = service.find_poll(&126809).await.unwrap();
+        let scores: Vec<(u64, u32)> = poll
+            .options
+            .into_iter()
+            .map(|option| (option.id, option.score))
+            .collect();
+
+        assert_eq!(scores, vec![(126810, 335), (126811, 12)]);
+    }
+
+    #[actix_rt::test]
+    async fn handles_poll_options_which_fail_to_be_fetched() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let poll = service.find_poll(&160704).await.unwrap();
+        let ids: Vec<u64> = poll.options.into_iter().map(|option| option.id).collect();
+
+        assert_eq!(ids, vec![160705]);
+
+        let service = HackerNewsService::new(api.base_url())
+            .with_unavailable_item_policy(UnavailableItemPolicy::Placeholder);
+        let poll = service.find_poll(&160704).await.unwrap();
+        let texts: Vec<Option<String>> =
+            poll.options.into_iter().map(|option| option.text).collect();
+
+        assert_eq!(
+            texts,
+            vec![
+                Some(String::from("Yes")),
+                Some(String::from("[unavailable]"))
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn finds_a_user_profile() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let profile = service.find_user_profile("dhouston", None).await.unwrap();
+        let ids: Vec<u64> = profile.items.into_iter().map(|item| item.id).collect();
+
+        assert_eq!(profile.user.karma, 5010);
+        assert_eq!(ids, vec![9272, 8863]);
+        assert!(!profile.has_more);
+    }
+
+    #[actix_rt::test]
+    async fn skips_submissions_which_fail_to_be_fetched() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let profile = service.find_user_profile("jl", None).await.unwrap();
+
+        assert_eq!(profile.user.karma, 2937);
+        assert!(profile.items.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn fails_to_find_unknown_users() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+
+        assert!(matches!(
+            service.find_user("nobody").await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn fails_to_find_unknown_items() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+
+        assert!(matches!(
+            service.find_story(&1).await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn reports_an_unreachable_api_as_unavailable() {
+        // reserve a port and release it right away so nothing listens on it
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let service = HackerNewsService::new(&format!("http://{}/v0", addr));
+
+        assert!(matches!(
+            service.find_story(&8863).await,
+            Err(Error::UpstreamUnavailable(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn finds_updates() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = HackerNewsService::new(api.base_url());
+        let updates = service.find_updates().await.unwrap();
+
+        assert_eq!(updates.items, vec![8863, 9224, 126809]);
+        assert_eq!(updates.profiles, vec!["dhouston", "pg"]);
+        assert!(service.find_max_item_id().await.is_ok());
+    }
+}
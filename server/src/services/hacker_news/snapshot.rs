@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+use super::Feed;
+
+/// Time during which a snapshot is handed out to clients requesting the
+/// first page of a feed, after this a fresh list is fetched from upstream.
+const SNAPSHOT_REFRESH: Duration = Duration::from_secs(60);
+
+/// Time during which a snapshot can be paged through with a cursor.
+const SNAPSHOT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// A feed's list of item IDs as it was when it was fetched, pages are taken
+/// from the same list so stories arriving in the meantime don't shift them.
+pub struct Snapshot {
+    pub version: u64,
+    pub feed: Feed,
+    pub ids: Vec<u64>,
+    created_at: Instant,
+}
+
+/// Position within a snapshot, handed to clients as an opaque token with
+/// the `<version>.<offset>` format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cursor {
+    pub version: u64,
+    pub offset: usize,
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.version, self.offset)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::BadRequest(format!("Invalid cursor: {}", s));
+        let mut parts = s.splitn(2, '.');
+        let version = parts.next().ok_or_else(invalid)?;
+        let offset = parts.next().ok_or_else(invalid)?;
+
+        Ok(Cursor {
+            version: version.parse().map_err(|_| invalid())?,
+            offset: offset.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// In-memory store of feed snapshots, expired snapshots are dropped as new
+/// ones are inserted.
+pub struct SnapshotStore {
+    snapshots: Mutex<HashMap<u64, Arc<Snapshot>>>,
+    next_version: AtomicU64,
+}
+
+impl SnapshotStore {
+    pub fn new() -> Self {
+        SnapshotStore {
+            snapshots: Mutex::new(HashMap::new()),
+            next_version: AtomicU64::new(1),
+        }
+    }
+
+    pub fn get(&self, version: u64) -> Option<Arc<Snapshot>> {
+        let snapshots = self.snapshots.lock().unwrap();
+
+        snapshots
+            .get(&version)
+            .filter(|snapshot| snapshot.created_at.elapsed() < SNAPSHOT_TTL)
+            .cloned()
+    }
+
+    /// Finds the most recent snapshot of `feed` if it is fresh enough to be
+    /// handed out as the first page.
+    pub fn latest(&self, feed: Feed) -> Option<Arc<Snapshot>> {
+        let snapshots = self.snapshots.lock().unwrap();
+
+        snapshots
+            .values()
+            .filter(|snapshot| snapshot.feed == feed)
+            .filter(|snapshot| snapshot.created_at.elapsed() < SNAPSHOT_REFRESH)
+            .max_by_key(|snapshot| snapshot.version)
+            .cloned()
+    }
+
+    pub fn insert(&self, feed: Feed, ids: Vec<u64>) -> Arc<Snapshot> {
+        let snapshot = Arc::new(Snapshot {
+            version: self.next_version.fetch_add(1, Ordering::Relaxed),
+            feed,
+            ids,
+            created_at: Instant::now(),
+        });
+        let mut snapshots = self.snapshots.lock().unwrap();
+
+        snapshots.retain(|_, snapshot| snapshot.created_at.elapsed() < SNAPSHOT_TTL);
+        snapshots.insert(snapshot.version, Arc::clone(&snapshot));
+
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{Cursor, Feed, SnapshotStore};
+
+    #[test]
+    fn parses_cursors() {
+        let cursor = Cursor {
+            version: 7,
+            offset: 40,
+        };
+
+        assert_eq!(Cursor::from_str(&cursor.to_string()).unwrap(), cursor);
+        assert!(Cursor::from_str("7").is_err());
+        assert!(Cursor::from_str("seven.40").is_err());
+    }
+
+    #[test]
+    fn hands_out_the_latest_snapshot_of_a_feed() {
+        let store = SnapshotStore::new();
+
+        store.insert(Feed::New, vec![3, 2, 1]);
+        store.insert(Feed::Top, vec![1, 2, 3]);
+        store.insert(Feed::New, vec![4, 3, 2, 1]);
+
+        assert_eq!(store.latest(Feed::New).unwrap().ids, vec![4, 3, 2, 1]);
+        assert_eq!(store.latest(Feed::Top).unwrap().ids, vec![1, 2, 3]);
+        assert!(store.latest(Feed::Ask).is_none());
+    }
+
+    #[test]
+    fn keeps_previous_snapshots_reachable() {
+        let store = SnapshotStore::new();
+        let first = store.insert(Feed::New, vec![3, 2, 1]);
+
+        store.insert(Feed::New, vec![4, 3, 2, 1]);
+
+        assert_eq!(store.get(first.version).unwrap().ids, vec![3, 2, 1]);
+    }
+}
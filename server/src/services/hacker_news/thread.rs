@@ -0,0 +1,132 @@
+use common::hacker_news::{Comment, CommentNode, ThreadEvent};
+use std::collections::{HashMap, HashSet};
+
+/// Changes which turn the `previous` thread into the `current` one. Added
+/// comments are listed parents first, so replies always have a place to go.
+pub fn diff_threads(previous: &[CommentNode], current: &[CommentNode]) -> Vec<ThreadEvent> {
+    let previous_comments: HashMap<u64, &Comment> = flatten(previous)
+        .into_iter()
+        .map(|(comment, _)| (comment.id, comment))
+        .collect();
+    let current_comments = flatten(current);
+    let current_ids: HashSet<u64> = current_comments
+        .iter()
+        .map(|(comment, _)| comment.id)
+        .collect();
+    let mut events = Vec::new();
+
+    for (comment, position) in current_comments {
+        match previous_comments.get(&comment.id) {
+            None => events.push(ThreadEvent::CommentAdded {
+                comment: comment.clone(),
+                position,
+            }),
+            // deleted and dead comments are replaced by placeholders which
+            // have no author
+            Some(previous) if previous.by.is_some() && comment.by.is_none() => {
+                events.push(ThreadEvent::CommentDeleted { id: comment.id })
+            }
+            Some(previous) if previous.text != comment.text => {
+                events.push(ThreadEvent::CommentEdited {
+                    comment: comment.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (comment, _) in flatten(previous) {
+        if !current_ids.contains(&comment.id) {
+            events.push(ThreadEvent::CommentDeleted { id: comment.id });
+        }
+    }
+
+    events
+}
+
+/// Every comment in the thread along with its position among its siblings,
+/// in depth-first order.
+fn flatten(nodes: &[CommentNode]) -> Vec<(&Comment, usize)> {
+    let mut comments = Vec::new();
+
+    for (position, node) in nodes.iter().enumerate() {
+        comments.push((&node.comment, position));
+        comments.extend(flatten(&node.replies));
+    }
+
+    comments
+}
+
+#[cfg(test)]
+mod test {
+    use common::hacker_news::{Comment, CommentNode, ThreadEvent, Type};
+
+    use super::diff_threads;
+
+    fn node(id: u64, text: &str, replies: Vec<CommentNode>) -> CommentNode {
+        CommentNode {
+            comment: Comment {
+                id,
+                r#type: Type::Comment,
+                by: Some(String::from("pg")),
+                kids: Some(replies.iter().map(|reply| reply.comment.id).collect()),
+                parent: None,
+                text: Some(String::from(text)),
+                time: 1175714200,
+            },
+            replies,
+        }
+    }
+
+    #[test]
+    fn finds_no_changes_in_the_same_thread() {
+        let thread = vec![node(1, "first", vec![node(2, "reply", vec![])])];
+
+        assert!(diff_threads(&thread, &thread).is_empty());
+    }
+
+    #[test]
+    fn finds_added_replies_parents_first() {
+        let previous = vec![node(1, "first", vec![node(2, "reply", vec![])])];
+        let current = vec![node(
+            1,
+            "first",
+            vec![
+                node(3, "newer reply", vec![node(4, "nested", vec![])]),
+                node(2, "reply", vec![]),
+            ],
+        )];
+        let events = diff_threads(&previous, &current);
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            ThreadEvent::CommentAdded { comment, position: 0 } if comment.id == 3
+        ));
+        assert!(matches!(
+            &events[1],
+            ThreadEvent::CommentAdded { comment, position: 0 } if comment.id == 4
+        ));
+    }
+
+    #[test]
+    fn finds_edited_and_deleted_comments() {
+        let previous = vec![
+            node(1, "first", vec![node(2, "reply", vec![])]),
+            node(3, "second", vec![]),
+        ];
+        let mut current = vec![node(1, "[deleted]", vec![node(2, "edited", vec![])])];
+
+        current[0].comment.by = None;
+
+        let events = diff_threads(&previous, &current);
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0], ThreadEvent::CommentDeleted { id: 1 });
+        assert!(matches!(
+            &events[1],
+            ThreadEvent::CommentEdited { comment } if comment.text.as_deref() == Some("edited")
+        ));
+        assert_eq!(events[2], ThreadEvent::CommentDeleted { id: 3 });
+    }
+}
@@ -0,0 +1,111 @@
+use actix_web::rt::time::interval;
+use common::hacker_news::{CommentNode, ThreadEvent};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+use super::{diff_threads, HackerNewsService};
+
+/// Time between walks of a watched story's thread.
+const THREAD_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Events kept for watchers which fall behind, older events are dropped.
+const THREAD_CHANNEL_CAPACITY: usize = 256;
+
+/// Walks the threads of watched stories straight from upstream, so edits
+/// and replies show up on the next walk instead of once cached copies
+/// expire. Each story is walked once no matter how many watch it, and the
+/// changes are broadcast to every watcher.
+pub struct ThreadWatcher {
+    service: Arc<HackerNewsService>,
+    watched: Mutex<HashMap<u64, Sender<ThreadEvent>>>,
+}
+
+impl ThreadWatcher {
+    pub fn new(service: Arc<HackerNewsService>) -> Self {
+        ThreadWatcher {
+            service,
+            watched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Subscribes to the changes of the thread of story `id`, its walks are
+    /// started along with the first subscription.
+    pub fn watch(self: &Arc<Self>, id: u64) -> Receiver<ThreadEvent> {
+        let mut watched = self.watched.lock().unwrap();
+
+        if let Some(sender) = watched.get(&id) {
+            return sender.subscribe();
+        }
+
+        let (sender, receiver) = broadcast::channel(THREAD_CHANNEL_CAPACITY);
+
+        watched.insert(id, sender.clone());
+        actix_web::rt::spawn(Arc::clone(self).run(id, sender));
+
+        receiver
+    }
+
+    /// Walks the thread of story `id` until nobody watches it anymore.
+    async fn run(self: Arc<Self>, id: u64, sender: Sender<ThreadEvent>) {
+        let mut interval = interval(THREAD_POLL_INTERVAL);
+        let mut thread: Option<Vec<CommentNode>> = None;
+
+        loop {
+            interval.tick().await;
+
+            if sender.receiver_count() == 0 {
+                let mut watched = self.watched.lock().unwrap();
+
+                // subscriptions are taken with the lock held, so nobody can
+                // subscribe to the sender once it's removed
+                if sender.receiver_count() == 0 {
+                    watched.remove(&id);
+                    return;
+                }
+            }
+
+            match self.service.refresh_story_thread(&id).await {
+                Ok(current) => {
+                    // the first walk only learns which comments are there
+                    if let Some(previous) = thread.as_ref() {
+                        for event in diff_threads(previous, &current) {
+                            // sending only fails when there are no watchers
+                            let _ = sender.send(event);
+                        }
+                    }
+
+                    thread = Some(current);
+                }
+                Err(err) => eprintln!(
+                    "An error ocurred walking the thread of story {}:\n{:?}",
+                    id, err
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::testing::hacker_news_api::FakeHackerNewsApi;
+
+    use super::{HackerNewsService, ThreadWatcher};
+
+    #[actix_rt::test]
+    async fn walks_each_story_once_for_every_watcher() {
+        let api = FakeHackerNewsApi::spawn();
+        let service = Arc::new(HackerNewsService::new(api.base_url()));
+        let watcher = Arc::new(ThreadWatcher::new(service));
+        let _first = watcher.watch(8863);
+        let _second = watcher.watch(8863);
+        let _other = watcher.watch(121003);
+        let watched = watcher.watched.lock().unwrap();
+
+        assert_eq!(watched.len(), 2);
+        assert_eq!(watched[&8863].receiver_count(), 2);
+    }
+}
@@ -0,0 +1,486 @@
+use actix_web::rt::time::interval;
+use actix_web::web::block;
+use chrono::{DateTime, Utc};
+use image::imageops::FilterType;
+use image::io::Reader;
+use image::{DynamicImage, GenericImageView, ImageOutputFormat};
+use reqwest::header;
+use serde::Deserialize;
+use sqlx::{query, query_as, FromRow, PgPool};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::services::link_preview::failure_backoff;
+use crate::services::link_preview::fetch::{fetch, read_body};
+use crate::utils::sha256;
+
+/// Largest original image the proxy is willing to download, in bytes.
+const MAX_IMAGE_SIZE: usize = 5 * 1024 * 1024;
+
+/// Largest width and height of an original image, images are decoded in
+/// memory so these keep decompression bombs out.
+const MAX_IMAGE_DIMENSION: u32 = 8000;
+
+/// Quality of the JPEG encoded thumbnails, icons are encoded as PNG to
+/// keep their transparency.
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// Images which no preview nor article registered for this long are purged,
+/// along with their thumbnails.
+const IMAGE_RETENTION: Duration = Duration::from_secs(60 * 24 * 60 * 60);
+
+/// Time between purges of images beyond their retention.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Sizes thumbnails are generated in, every size is generated when an image
+/// is first requested.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailSize {
+    /// Images in the story cards of the home page
+    Card,
+    /// Images at the top of a story page
+    Hero,
+    /// Favicons of the sites stories link to
+    Icon,
+}
+
+impl ThumbnailSize {
+    const ALL: [ThumbnailSize; 3] = [
+        ThumbnailSize::Card,
+        ThumbnailSize::Hero,
+        ThumbnailSize::Icon,
+    ];
+
+    /// Bounds thumbnails fit in, images are never scaled up.
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            ThumbnailSize::Card => (480, 270),
+            ThumbnailSize::Hero => (1200, 630),
+            ThumbnailSize::Icon => (64, 64),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ThumbnailSize::Card => "card",
+            ThumbnailSize::Hero => "hero",
+            ThumbnailSize::Icon => "icon",
+        }
+    }
+}
+
+impl Default for ThumbnailSize {
+    fn default() -> Self {
+        ThumbnailSize::Card
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Thumbnail {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(FromRow)]
+struct ImagesRow {
+    url: String,
+    failures: i32,
+    retry_at: Option<DateTime<Utc>>,
+}
+
+/// Proxies preview images so readers never load them from their hosts,
+/// thumbnails are stored in the `thumbnails` table by the hash of the
+/// original URL.
+#[derive(Clone)]
+pub struct ImageService {
+    database_pool: Arc<PgPool>,
+    /// URL the server is reachable at, proxied URLs are absolute
+    public_url: String,
+    /// Locks of the images being fetched, requests for an image which is
+    /// being fetched wait for its thumbnails instead of fetching it again
+    fetching: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl ImageService {
+    pub fn new(database_pool: Arc<PgPool>, public_url: &str) -> Self {
+        ImageService {
+            database_pool,
+            public_url: public_url.trim_end_matches('/').to_string(),
+            fetching: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// URL the image at `url` is served from by the proxy
+    pub fn proxied_url(&self, url: &str) -> String {
+        format!("{}/api/v1/images/{}", self.public_url, sha256::hash(url))
+    }
+
+    /// Makes the image at `url` available through its proxied URL, images
+    /// are kept for as long as these keep being registered.
+    pub async fn register(&self, url: &str) -> Result<()> {
+        query(
+            r#"
+        INSERT INTO images (url_hash, url) VALUES ($1, $2)
+        ON CONFLICT (url_hash) DO UPDATE SET registered_at = CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(sha256::hash(url))
+        .bind(url)
+        .execute(&*self.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Finds a thumbnail of the image hashed as `url_hash`, thumbnails are
+    /// generated from the original image the first time these are requested.
+    /// Images which fail to be fetched or decoded aren't tried again until
+    /// their backoff passes.
+    pub async fn find_thumbnail(&self, url_hash: &str, size: ThumbnailSize) -> Result<Thumbnail> {
+        if let Some(thumbnail) = self.find_stored_thumbnail(url_hash, size).await? {
+            return Ok(thumbnail);
+        }
+
+        let lock = Arc::clone(
+            self.fetching
+                .lock()
+                .unwrap()
+                .entry(url_hash.to_string())
+                .or_default(),
+        );
+        let _guard = lock.lock().await;
+        let thumbnail = self.generate_thumbnail(url_hash, size).await;
+        let mut fetching = self.fetching.lock().unwrap();
+
+        // requests arriving from now on find the thumbnails stored
+        if let Some(current) = fetching.get(url_hash) {
+            if Arc::ptr_eq(current, &lock) {
+                fetching.remove(url_hash);
+            }
+        }
+
+        thumbnail
+    }
+
+    /// Purges images beyond their retention for as long as the server runs.
+    pub async fn run_purge(self) {
+        let mut interval = interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.purge_unregistered_images().await {
+                eprintln!("An error ocurred purging images:\n{:?}", err);
+            }
+        }
+    }
+
+    async fn find_stored_thumbnail(
+        &self,
+        url_hash: &str,
+        size: ThumbnailSize,
+    ) -> Result<Option<Thumbnail>> {
+        let thumbnail =
+            query_as("SELECT content_type, data FROM thumbnails WHERE url_hash = $1 AND size = $2")
+                .bind(url_hash)
+                .bind(size.name())
+                .fetch_optional(&*self.database_pool)
+                .await?;
+
+        Ok(thumbnail)
+    }
+
+    /// Fetches the image hashed as `url_hash` and stores its thumbnails,
+    /// failures are stored to be retried later.
+    async fn generate_thumbnail(&self, url_hash: &str, size: ThumbnailSize) -> Result<Thumbnail> {
+        // the image could have been fetched by the request holding the lock
+        if let Some(thumbnail) = self.find_stored_thumbnail(url_hash, size).await? {
+            return Ok(thumbnail);
+        }
+
+        let row: Option<ImagesRow> =
+            query_as("SELECT url, failures, retry_at FROM images WHERE url_hash = $1")
+                .bind(url_hash)
+                .fetch_optional(&*self.database_pool)
+                .await?;
+        let row = match row {
+            Some(row) => row,
+            None => {
+                return Err(Error::NotFound(format!(
+                    "There's no image with hash: {}",
+                    url_hash
+                )))
+            }
+        };
+
+        if let Some(retry_at) = row.retry_at.filter(|retry_at| *retry_at > Utc::now()) {
+            return Err(Error::ExternalUnavailable(format!(
+                "The image failed to be fetched, it's tried again after {}",
+                retry_at.to_rfc3339()
+            )));
+        }
+
+        let thumbnails = match self.fetch_thumbnails(&row.url).await {
+            Ok(thumbnails) => thumbnails,
+            Err(err) => {
+                let failures = row.failures as u32 + 1;
+
+                if let Err(err) = self.store_failure(url_hash, failures).await {
+                    eprintln!("An error ocurred storing the image:\n{:?}", err);
+                }
+
+                return Err(err);
+            }
+        };
+        let mut requested = None;
+
+        for (thumbnail_size, width, height, thumbnail) in thumbnails {
+            query(
+                r#"
+            INSERT INTO thumbnails (
+                url_hash,
+                size,
+                content_type,
+                width,
+                height,
+                data
+            ) VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                $5,
+                $6
+            ) ON CONFLICT (url_hash, size) DO NOTHING
+            "#,
+            )
+            .bind(url_hash)
+            .bind(thumbnail_size.name())
+            .bind(thumbnail.content_type.as_str())
+            .bind(width as i32)
+            .bind(height as i32)
+            .bind(thumbnail.data.as_slice())
+            .execute(&*self.database_pool)
+            .await?;
+
+            if thumbnail_size == size {
+                requested = Some(thumbnail);
+            }
+        }
+
+        if row.failures > 0 {
+            self.store_failure(url_hash, 0).await?;
+        }
+
+        requested.ok_or_else(|| Error::Internal(String::from("Missing thumbnail size")))
+    }
+
+    /// Downloads the image at `url` and makes its thumbnails.
+    async fn fetch_thumbnails(
+        &self,
+        url: &str,
+    ) -> Result<Vec<(ThumbnailSize, u32, u32, Thumbnail)>> {
+        let original = self.fetch_image(url).await?;
+
+        // decoding and resizing are CPU bound, these are kept off the
+        // threads serving requests
+        block(move || make_thumbnails(&original))
+            .await
+            .map_err(|err| Error::Internal(err.to_string()))?
+    }
+
+    /// The image isn't fetched again until the backoff of `failures`
+    /// consecutive failures passes, no failures at all clear its backoff.
+    async fn store_failure(&self, url_hash: &str, failures: u32) -> Result<()> {
+        let retry_at = match failures {
+            0 => None,
+            _ => Some(Utc::now() + chrono::Duration::from_std(failure_backoff(failures)).unwrap()),
+        };
+
+        query("UPDATE images SET failures = $2, retry_at = $3 WHERE url_hash = $1")
+            .bind(url_hash)
+            .bind(failures as i32)
+            .bind(retry_at)
+            .execute(&*self.database_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn purge_unregistered_images(&self) -> Result<()> {
+        query("DELETE FROM images WHERE registered_at < $1")
+            .bind(Utc::now() - chrono::Duration::from_std(IMAGE_RETENTION).unwrap())
+            .execute(&*self.database_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Downloads an original image, hosts answering with something other
+    /// than an image or with an image too large are refused.
+    async fn fetch_image(&self, url: &str) -> Result<Vec<u8>> {
+        let response = fetch(url).await?;
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        if !content_type.starts_with("image/") {
+            return Err(Error::ExternalUnavailable(format!(
+                "Expected an image, found: {}",
+                content_type
+            )));
+        }
+
+        read_body(response, MAX_IMAGE_SIZE).await
+    }
+}
+
+/// Decodes an original image and encodes a thumbnail for every
+/// `ThumbnailSize`, along with the dimensions of each thumbnail.
+fn make_thumbnails(original: &[u8]) -> Result<Vec<(ThumbnailSize, u32, u32, Thumbnail)>> {
+    let reader = || {
+        Reader::new(Cursor::new(original))
+            .with_guessed_format()
+            .map_err(|err| Error::ExternalUnavailable(format!("Unreadable image: {}", err)))
+    };
+    let (width, height) = reader()?
+        .into_dimensions()
+        .map_err(|err| Error::ExternalUnavailable(format!("Unreadable image: {}", err)))?;
+
+    if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        return Err(Error::ExternalUnavailable(format!(
+            "The image is larger than {}x{} pixels",
+            MAX_IMAGE_DIMENSION, MAX_IMAGE_DIMENSION
+        )));
+    }
+
+    let image = reader()?
+        .decode()
+        .map_err(|err| Error::ExternalUnavailable(format!("Unreadable image: {}", err)))?;
+
+    ThumbnailSize::ALL
+        .iter()
+        .map(|size| {
+            let thumbnail = resize(&image, *size);
+
+            Ok((
+                *size,
+                thumbnail.width(),
+                thumbnail.height(),
+                encode(thumbnail, *size)?,
+            ))
+        })
+        .collect()
+}
+
+fn encode(thumbnail: DynamicImage, size: ThumbnailSize) -> Result<Thumbnail> {
+    let mut data = Vec::new();
+    let (thumbnail, format, content_type) = match size {
+        ThumbnailSize::Icon => (thumbnail, ImageOutputFormat::Png, "image/png"),
+        // JPEG has no alpha channel
+        _ => (
+            DynamicImage::ImageRgb8(thumbnail.to_rgb8()),
+            ImageOutputFormat::Jpeg(THUMBNAIL_QUALITY),
+            "image/jpeg",
+        ),
+    };
+
+    thumbnail
+        .write_to(&mut data, format)
+        .map_err(|err| Error::Internal(format!("Failed to encode thumbnail: {}", err)))?;
+
+    Ok(Thumbnail {
+        content_type: String::from(content_type),
+        data,
+    })
+}
+
+/// Scales an image down to fit in the bounds of `size`, keeping its aspect
+/// ratio.
+fn resize(image: &DynamicImage, size: ThumbnailSize) -> DynamicImage {
+    let (max_width, max_height) = size.dimensions();
+
+    if image.width() <= max_width && image.height() <= max_height {
+        return image.clone();
+    }
+
+    image.resize(max_width, max_height, FilterType::Triangle)
+}
+
+#[cfg(test)]
+mod test {
+    use image::{DynamicImage, ImageOutputFormat, RgbImage};
+    use std::sync::Arc;
+
+    use crate::error::Error;
+    use crate::testing::database_pool;
+    use crate::utils::sha256;
+
+    use super::{make_thumbnails, ImageService, ThumbnailSize};
+
+    fn encode_png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut data, ImageOutputFormat::Png)
+            .unwrap();
+
+        data
+    }
+
+    #[test]
+    fn scales_images_down_to_every_size() {
+        let thumbnails = make_thumbnails(&encode_png(2400, 1260)).unwrap();
+
+        assert_eq!(thumbnails.len(), 3);
+        assert_eq!(thumbnails[0].0, ThumbnailSize::Card);
+        assert_eq!((thumbnails[0].1, thumbnails[0].2), (480, 252));
+        assert_eq!(thumbnails[1].0, ThumbnailSize::Hero);
+        assert_eq!((thumbnails[1].1, thumbnails[1].2), (1200, 630));
+        assert_eq!(thumbnails[1].3.content_type, "image/jpeg");
+        assert_eq!(thumbnails[2].0, ThumbnailSize::Icon);
+        assert_eq!(thumbnails[2].1, 64);
+        assert_eq!(thumbnails[2].3.content_type, "image/png");
+    }
+
+    #[test]
+    fn never_scales_images_up() {
+        let thumbnails = make_thumbnails(&encode_png(48, 30)).unwrap();
+
+        assert!(thumbnails
+            .iter()
+            .all(|(_, width, height, _)| (*width, *height) == (48, 30)));
+    }
+
+    #[test]
+    fn refuses_data_which_is_not_an_image() {
+        assert!(make_thumbnails(b"<html></html>").is_err());
+    }
+
+    #[actix_rt::test]
+    async fn stores_images_which_fail_to_be_fetched() {
+        let database_pool = match database_pool().await {
+            Some(database_pool) => Arc::new(database_pool),
+            None => return,
+        };
+        let service = ImageService::new(database_pool, "http://0.0.0.0:3000");
+        // private addresses are never fetched
+        let url = "http://127.0.0.1/failing-image.png";
+        let url_hash = sha256::hash(url);
+
+        service.register(url).await.unwrap();
+        service.store_failure(&url_hash, 0).await.unwrap();
+
+        let first = service.find_thumbnail(&url_hash, ThumbnailSize::Card).await;
+        let second = service.find_thumbnail(&url_hash, ThumbnailSize::Icon).await;
+
+        assert!(matches!(first, Err(Error::ForbiddenUrl(_))));
+        assert!(matches!(second, Err(Error::ExternalUnavailable(_))));
+    }
+}
@@ -0,0 +1,332 @@
+//! Fetching of pages and images from URLs anyone can submit, requests are
+//! only made to hosts on the public internet so the server can't be used to
+//! reach itself, its cloud metadata endpoints or internal services.
+
+use actix_web::rt::time::timeout;
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Response, Url};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::lookup_host;
+
+use crate::error::{Error, Result};
+
+/// Largest page fetched, in bytes.
+const MAX_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// Maximum number of redirects followed before giving up.
+const MAX_REDIRECTS: usize = 5;
+
+/// Time given to a single request to be answered, body included.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time given to a host to accept the connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time given to a fetch to be answered, redirects included.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Fetches `url` following up to `MAX_REDIRECTS` redirects, every URL on
+/// the way is checked and its host resolved before it is requested.
+/// URLs which can't be fetched are answered with `Error::ForbiddenUrl`.
+pub async fn fetch(url: &str) -> Result<Response> {
+    let url = Url::parse(url).map_err(|err| Error::BadRequest(format!("Invalid URL: {}", err)))?;
+
+    match timeout(FETCH_TIMEOUT, follow_redirects(url)).await {
+        Ok(result) => result,
+        Err(_) => Err(Error::ExternalTimeout(format!(
+            "The URL wasn't fetched in {} seconds",
+            FETCH_TIMEOUT.as_secs()
+        ))),
+    }
+}
+
+/// Body of the page at `url` along with the URL it was found at, redirects
+/// included.
+pub async fn fetch_page(url: &str) -> Result<(String, Url)> {
+    let response = fetch(url).await?;
+    let page_url = response.url().clone();
+    let body = read_body(response, MAX_PAGE_SIZE).await?;
+
+    Ok((String::from_utf8_lossy(&body).into_owned(), page_url))
+}
+
+/// Reads the body of a response, bodies larger than `max_size` bytes are
+/// refused. Hosts could leave the length out or lie about it, so the body is
+/// read a chunk at a time.
+pub async fn read_body(mut response: Response, max_size: usize) -> Result<Vec<u8>> {
+    let too_large =
+        || Error::ExternalUnavailable(format!("The response is larger than {} bytes", max_size));
+
+    if let Some(length) = response.content_length() {
+        if length as usize > max_size {
+            return Err(too_large());
+        }
+    }
+
+    let mut body = Vec::new();
+
+    while let Some(chunk) = response.chunk().await.map_err(Error::external)? {
+        if body.len() + chunk.len() > max_size {
+            return Err(too_large());
+        }
+
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
+async fn follow_redirects(mut url: Url) -> Result<Response> {
+    for _ in 0..=MAX_REDIRECTS {
+        let address = resolve(&url).await?;
+        let response = client_for(&url, address)?
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(Error::external)?;
+
+        if !response.status().is_redirection() {
+            return response.error_for_status().map_err(Error::external);
+        }
+
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                Error::ExternalUnavailable(String::from("Redirected without a location"))
+            })?;
+
+        url = redirect_url(&url, location)?;
+    }
+
+    Err(Error::ExternalUnavailable(format!(
+        "Redirected more than {} times",
+        MAX_REDIRECTS
+    )))
+}
+
+/// A client which connects to `address` for the host of `url`, so the
+/// address which was checked is the one requested even if the host resolves
+/// somewhere else by the time the connection is made. Redirects are followed
+/// by hand, as every one of them is checked as well.
+fn client_for(url: &Url, address: SocketAddr) -> Result<Client> {
+    let mut builder = Client::builder()
+        .redirect(Policy::none())
+        .no_proxy()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT);
+
+    if let Some(host) = url.host_str() {
+        builder = builder.resolve(host, address);
+    }
+
+    builder
+        .build()
+        .map_err(|err| Error::Internal(format!("Failed to build HTTP client: {}", err)))
+}
+
+/// URL a response redirects to, relative locations are resolved against the
+/// URL which was requested.
+fn redirect_url(url: &Url, location: &str) -> Result<Url> {
+    let target = url
+        .join(location)
+        .map_err(|err| Error::ExternalUnavailable(format!("Invalid redirect: {}", err)))?;
+
+    check_scheme(&target)?;
+
+    Ok(target)
+}
+
+/// Resolves the host of `url`, every address it resolves to must be public
+/// as the one connected to can't be chosen.
+async fn resolve(url: &Url) -> Result<SocketAddr> {
+    check_scheme(url)?;
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| Error::ForbiddenUrl(String::from("The URL has no host")))?;
+    // IPv6 hosts are kept in brackets
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addresses: Vec<SocketAddr> = lookup_host((host, port))
+        .await
+        .map_err(|err| Error::ExternalUnavailable(format!("Failed to resolve {}: {}", host, err)))?
+        .collect();
+
+    for address in addresses.iter() {
+        if !is_public(address.ip()) {
+            return Err(Error::ForbiddenUrl(format!(
+                "The URL resolves to an address which isn't public: {}",
+                address.ip()
+            )));
+        }
+    }
+
+    addresses
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::ExternalUnavailable(format!("{} resolves to no address", host)))
+}
+
+fn check_scheme(url: &Url) -> Result<()> {
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(Error::ForbiddenUrl(format!(
+            "Only http and https URLs are fetched, found: {}",
+            scheme
+        ))),
+    }
+}
+
+fn is_public(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => is_public_ipv4(address),
+        IpAddr::V6(address) => is_public_ipv6(address),
+    }
+}
+
+fn is_public_ipv4(address: Ipv4Addr) -> bool {
+    let [a, b, c, _] = address.octets();
+
+    !(a == 0 // "this" network
+        || address.is_loopback()
+        || address.is_private()
+        || address.is_link_local()
+        // shared address space, 100.64.0.0/10
+        || (a == 100 && (b & 0xc0) == 64)
+        // protocol assignments, 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        || address.is_documentation()
+        // benchmarking, 198.18.0.0/15
+        || (a == 198 && (b & 0xfe) == 18)
+        || address.is_multicast()
+        // reserved, the broadcast address included
+        || a >= 240)
+}
+
+fn is_public_ipv6(address: Ipv6Addr) -> bool {
+    // IPv4-mapped and IPv4-compatible addresses, `::1` and `::` included,
+    // are checked as the IPv4 address these hold
+    if let Some(address) = address.to_ipv4() {
+        return is_public_ipv4(address);
+    }
+
+    let segments = address.segments();
+
+    !(address.is_multicast()
+        // unique local, fc00::/7
+        || (segments[0] & 0xfe00) == 0xfc00
+        // link local, fe80::/10
+        || (segments[0] & 0xffc0) == 0xfe80
+        // site local, fec0::/10
+        || (segments[0] & 0xffc0) == 0xfec0
+        // NAT64, 64:ff9b::/96, reaches IPv4 addresses through a gateway
+        || (segments[0] == 0x64 && segments[1] == 0xff9b)
+        // discard only, 100::/64
+        || (segments[0] == 0x100 && segments[1..4] == [0, 0, 0])
+        // documentation, 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::Url;
+
+    use crate::error::Error;
+
+    use super::{redirect_url, resolve};
+
+    async fn is_forbidden(url: &str) -> bool {
+        matches!(
+            resolve(&Url::parse(url).unwrap()).await,
+            Err(Error::ForbiddenUrl(_))
+        )
+    }
+
+    #[actix_rt::test]
+    async fn rejects_schemes_other_than_http() {
+        assert!(is_forbidden("file:///etc/passwd").await);
+        assert!(is_forbidden("ftp://93.184.216.34/").await);
+        assert!(is_forbidden("gopher://93.184.216.34:70/").await);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_loopback_addresses() {
+        assert!(is_forbidden("http://127.0.0.1/").await);
+        assert!(is_forbidden("http://127.1.2.3:8080/").await);
+        assert!(is_forbidden("http://localhost:3000/").await);
+        assert!(is_forbidden("http://[::1]/").await);
+        // hosts written as a single number are parsed as IPv4 addresses
+        assert!(is_forbidden("http://2130706433/").await);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_private_addresses() {
+        assert!(is_forbidden("http://10.0.0.1/").await);
+        assert!(is_forbidden("http://172.16.5.4/").await);
+        assert!(is_forbidden("http://192.168.1.1/").await);
+        assert!(is_forbidden("http://100.64.0.1/").await);
+        assert!(is_forbidden("http://[fd12:3456:789a::1]/").await);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_link_local_addresses() {
+        assert!(is_forbidden("http://169.254.169.254/latest/meta-data/").await);
+        assert!(is_forbidden("http://[fe80::1]/").await);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_reserved_addresses() {
+        assert!(is_forbidden("http://0.0.0.0/").await);
+        assert!(is_forbidden("http://192.0.0.8/").await);
+        assert!(is_forbidden("http://198.18.0.1/").await);
+        assert!(is_forbidden("http://203.0.113.7/").await);
+        assert!(is_forbidden("http://224.0.0.1/").await);
+        assert!(is_forbidden("http://240.0.0.1/").await);
+        assert!(is_forbidden("http://255.255.255.255/").await);
+        assert!(is_forbidden("http://[::]/").await);
+        assert!(is_forbidden("http://[ff02::1]/").await);
+        assert!(is_forbidden("http://[2001:db8::1]/").await);
+        assert!(is_forbidden("http://[64:ff9b::a9fe:a9fe]/").await);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_ipv4_addresses_written_as_ipv6() {
+        assert!(is_forbidden("http://[::ffff:127.0.0.1]/").await);
+        assert!(is_forbidden("http://[::ffff:a9fe:a9fe]/").await);
+        assert!(is_forbidden("http://[::10.0.0.1]/").await);
+    }
+
+    #[actix_rt::test]
+    async fn rejects_redirects_out_of_the_public_internet() {
+        let url = Url::parse("https://example.com/story").unwrap();
+        let redirect = redirect_url(&url, "http://169.254.169.254/latest/meta-data/").unwrap();
+
+        assert!(matches!(
+            redirect_url(&url, "file:///etc/passwd"),
+            Err(Error::ForbiddenUrl(_))
+        ));
+        assert!(matches!(
+            resolve(&redirect).await,
+            Err(Error::ForbiddenUrl(_))
+        ));
+        assert_eq!(
+            redirect_url(&url, "/moved").unwrap().as_str(),
+            "https://example.com/moved"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn accepts_public_addresses() {
+        let url = Url::parse("https://93.184.216.34/").unwrap();
+
+        assert_eq!(
+            resolve(&url).await.unwrap(),
+            "93.184.216.34:443".parse().unwrap()
+        );
+        assert!(!is_forbidden("http://[2606:4700:4700::1111]/").await);
+    }
+}
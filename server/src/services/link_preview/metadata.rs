@@ -0,0 +1,280 @@
+//! Metadata of a page beyond the title, description and image found by the
+//! `link_preview` crate, taken from Open Graph, Twitter card and plain HTML
+//! tags.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use common::{Embed, EmbedKind};
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+use super::oembed;
+
+/// Words read in a minute, used to estimate reading times.
+const WORDS_PER_MINUTE: usize = 230;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Metadata {
+    pub site_name: Option<String>,
+    pub favicon_url: Option<String>,
+    pub canonical_url: Option<String>,
+    pub content_type: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub reading_time: Option<u32>,
+    /// Player described by a Twitter card, for pages without oEmbed
+    pub player: Option<Embed>,
+    /// oEmbed endpoint advertised by the page
+    pub oembed_url: Option<Url>,
+}
+
+/// Finds the metadata of the page fetched from `url`, relative URLs are
+/// resolved against it.
+pub fn parse(html: &Html, url: &Url) -> Metadata {
+    let site_name = meta(html, "og:site_name");
+    let player = twitter_player(html, url, site_name.clone());
+
+    Metadata {
+        site_name,
+        // browsers look for a favicon at the root when none is declared
+        favicon_url: link(html, "link[rel~=\"icon\"]", url)
+            .or_else(|| absolute(url, "/favicon.ico")),
+        canonical_url: link(html, "link[rel=\"canonical\"]", url)
+            .or_else(|| meta(html, "og:url").and_then(|href| absolute(url, &href))),
+        content_type: meta(html, "og:type"),
+        published_at: meta(html, "article:published_time")
+            .or_else(|| meta_itemprop(html, "datePublished"))
+            .and_then(|published_at| parse_datetime(&published_at)),
+        reading_time: reading_time(html),
+        player,
+        oembed_url: link(
+            html,
+            "link[rel=\"alternate\"][type=\"application/json+oembed\"]",
+            url,
+        )
+        .and_then(|href| Url::parse(&href).ok()),
+    }
+}
+
+/// Content of the first `meta` tag with `name` as either its property or
+/// its name, Open Graph uses the former while Twitter cards use the latter.
+fn meta(html: &Html, name: &str) -> Option<String> {
+    let selector = Selector::parse(&format!(
+        "meta[property=\"{}\"], meta[name=\"{}\"]",
+        name, name
+    ))
+    .ok()?;
+
+    first_content(html, &selector, "content")
+}
+
+fn meta_itemprop(html: &Html, itemprop: &str) -> Option<String> {
+    let selector = Selector::parse(&format!("[itemprop=\"{}\"]", itemprop)).ok()?;
+
+    first_content(html, &selector, "content").or_else(|| first_content(html, &selector, "datetime"))
+}
+
+/// Absolute URL in the `href` of the first element matching `selector`
+fn link(html: &Html, selector: &str, url: &Url) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+
+    first_content(html, &selector, "href").and_then(|href| absolute(url, &href))
+}
+
+fn first_content(html: &Html, selector: &Selector, attribute: &str) -> Option<String> {
+    html.select(selector)
+        .filter_map(|element| element.value().attr(attribute))
+        .map(str::trim)
+        .find(|content| !content.is_empty())
+        .map(String::from)
+}
+
+/// Resolves `href` against `url`, only http and https URLs are kept.
+fn absolute(url: &Url, href: &str) -> Option<String> {
+    url.join(href)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .map(String::from)
+}
+
+/// Dates are either RFC 3339 timestamps or dates without time, which are
+/// taken as midnight UTC.
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d")
+        .ok()
+        .map(|date| DateTime::from_utc(date.and_hms(0, 0, 0), Utc))
+}
+
+fn twitter_player(html: &Html, url: &Url, provider_name: Option<String>) -> Option<Embed> {
+    if meta(html, "twitter:card").as_deref() != Some("player") {
+        return None;
+    }
+
+    let player_url = meta(html, "twitter:player")
+        .and_then(|href| url.join(&href).ok())
+        .filter(oembed::is_known_player)?;
+
+    Some(Embed {
+        kind: EmbedKind::Video,
+        provider_name,
+        author_name: meta(html, "twitter:creator"),
+        player_url: Some(String::from(player_url)),
+        text: None,
+        width: meta(html, "twitter:player:width").and_then(|width| width.parse().ok()),
+        height: meta(html, "twitter:player:height").and_then(|height| height.parse().ok()),
+    })
+}
+
+/// Minutes it takes to read the article in the page, or the whole body if
+/// there's no article. Pages read in less than a minute have no estimate.
+fn reading_time(html: &Html) -> Option<u32> {
+    let article = Selector::parse("article").ok()?;
+    let body = Selector::parse("body").ok()?;
+    let root = html
+        .select(&article)
+        .next()
+        .or_else(|| html.select(&body).next())?;
+    let words: usize = root
+        .descendants()
+        .filter_map(|node| {
+            let text = node.value().as_text()?;
+            let parent = node.parent()?.value().as_element()?;
+
+            match parent.name() {
+                "script" | "style" | "noscript" | "template" => None,
+                _ => Some(text.split_whitespace().count()),
+            }
+        })
+        .sum();
+
+    if words < WORDS_PER_MINUTE {
+        return None;
+    }
+
+    Some((words as f64 / WORDS_PER_MINUTE as f64).ceil() as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+    use common::EmbedKind;
+    use reqwest::Url;
+    use scraper::Html;
+
+    use super::parse;
+
+    fn page(head: &str, body: &str) -> Html {
+        Html::parse_document(&format!(
+            "<html><head>{}</head><body>{}</body></html>",
+            head, body
+        ))
+    }
+
+    fn url() -> Url {
+        Url::parse("https://blog.example.com/posts/rust?ref=hn").unwrap()
+    }
+
+    #[test]
+    fn finds_open_graph_and_link_metadata() {
+        let html = page(
+            r#"
+            <meta property="og:site_name" content="Example Blog">
+            <meta property="og:type" content="article">
+            <meta property="article:published_time" content="2021-10-19T14:02:33+02:00">
+            <link rel="shortcut icon" href="/static/icon.png">
+            <link rel="canonical" href="/posts/rust">
+            <link rel="alternate" type="application/json+oembed" href="https://blog.example.com/oembed?url=rust">
+            "#,
+            "<p>Hello</p>",
+        );
+        let metadata = parse(&html, &url());
+
+        assert_eq!(metadata.site_name.as_deref(), Some("Example Blog"));
+        assert_eq!(metadata.content_type.as_deref(), Some("article"));
+        assert_eq!(
+            metadata.published_at,
+            Some(Utc.ymd(2021, 10, 19).and_hms(12, 2, 33))
+        );
+        assert_eq!(
+            metadata.favicon_url.as_deref(),
+            Some("https://blog.example.com/static/icon.png")
+        );
+        assert_eq!(
+            metadata.canonical_url.as_deref(),
+            Some("https://blog.example.com/posts/rust")
+        );
+        assert_eq!(
+            metadata.oembed_url.map(String::from).as_deref(),
+            Some("https://blog.example.com/oembed?url=rust")
+        );
+        assert_eq!(metadata.reading_time, None);
+    }
+
+    #[test]
+    fn falls_back_to_the_root_favicon_and_dates_without_time() {
+        let html = page(
+            r#"<meta itemprop="datePublished" content="2021-10-19">"#,
+            "",
+        );
+        let metadata = parse(&html, &url());
+
+        assert_eq!(
+            metadata.favicon_url.as_deref(),
+            Some("https://blog.example.com/favicon.ico")
+        );
+        assert_eq!(
+            metadata.published_at,
+            Some(Utc.ymd(2021, 10, 19).and_hms(0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn finds_twitter_card_players() {
+        let html = page(
+            r#"
+            <meta name="twitter:card" content="player">
+            <meta name="twitter:player" content="https://player.vimeo.com/video/42">
+            <meta name="twitter:player:width" content="1280">
+            <meta name="twitter:player:height" content="720">
+            "#,
+            "",
+        );
+        let player = parse(&html, &url()).player.unwrap();
+
+        assert_eq!(player.kind, EmbedKind::Video);
+        assert_eq!(
+            player.player_url.as_deref(),
+            Some("https://player.vimeo.com/video/42")
+        );
+        assert_eq!((player.width, player.height), (Some(1280), Some(720)));
+    }
+
+    #[test]
+    fn leaves_players_of_unknown_hosts_out() {
+        let html = page(
+            r#"
+            <meta name="twitter:card" content="player">
+            <meta name="twitter:player" content="https://player.example.com/embed/42">
+            "#,
+            "",
+        );
+
+        assert_eq!(parse(&html, &url()).player, None);
+    }
+
+    #[test]
+    fn estimates_reading_time_out_of_the_article() {
+        let words = vec!["word"; 1000].join(" ");
+        let html = page(
+            "",
+            &format!(
+                "<nav>{}</nav><article><script>{}</script><p>{}</p></article>",
+                words, words, words
+            ),
+        );
+
+        assert_eq!(parse(&html, &url()).reading_time, Some(5));
+    }
+}
@@ -0,0 +1,592 @@
+pub mod fetch;
+mod metadata;
+mod oembed;
+
+use actix_web::rt::time::{interval, timeout, Instant};
+use chrono::{DateTime, TimeZone, Utc};
+use common::{Embed, LinkPreview};
+use futures::stream::{self, StreamExt};
+use link_preview::html::remove_html_tags;
+use reqwest::Url;
+use scraper::Html;
+use sqlx::types::Json;
+use sqlx::{query, query_as, FromRow, PgPool};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::services::images::ImageService;
+use crate::utils::sha256;
+
+use self::fetch::fetch_page;
+use self::oembed::fetch_oembed;
+
+/// Time a fetched preview is served before it is refreshed.
+const PREVIEW_TTL: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+
+/// Time before a URL which failed to be fetched is tried again, doubled on
+/// every consecutive failure.
+const FAILURE_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+/// Longest time a URL which keeps failing is left alone.
+const MAX_FAILURE_BACKOFF: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Maximum number of pages fetched at once for a batch of previews.
+const PREVIEW_FETCH_CONCURRENCY: usize = 8;
+
+/// Time a batch waits for the previews which have to be fetched
+const BATCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Previews which nobody requested for this long after expiring are purged.
+const PREVIEW_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Time between purges of previews beyond their retention.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Link previews stored in the `previews` table by URL hash. Expired
+/// previews are served while these are refreshed in the background, and
+/// URLs which fail to be fetched are stored as well, so these are only
+/// retried once their backoff expires. Preview images are served through
+/// the image proxy.
+#[derive(Clone)]
+pub struct LinkPreviewService {
+    database_pool: Arc<PgPool>,
+    images: Arc<ImageService>,
+    /// Hashes of the URLs being refreshed in the background
+    refreshing: Arc<Mutex<HashSet<String>>>,
+}
+
+#[derive(Debug, FromRow)]
+struct PreviewsRow {
+    id: Uuid,
+    title: Option<String>,
+    description: Option<String>,
+    domain: Option<String>,
+    url_hash: String,
+    image_url: Option<String>,
+    site_name: Option<String>,
+    favicon_url: Option<String>,
+    canonical_url: Option<String>,
+    content_type: Option<String>,
+    published_at: Option<DateTime<Utc>>,
+    reading_time: Option<i32>,
+    embed: Option<Json<Embed>>,
+    expires_at: DateTime<Utc>,
+    failed: bool,
+    failures: i32,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// A stored preview, `preview` is `None` if the URL failed to be fetched.
+struct CachedPreview {
+    preview: Option<LinkPreview>,
+    failures: u32,
+    expires_at: DateTime<Utc>,
+}
+
+impl From<PreviewsRow> for LinkPreview {
+    fn from(row: PreviewsRow) -> Self {
+        LinkPreview {
+            title: row.title,
+            image_url: row.image_url,
+            description: row.description,
+            domain: row.domain,
+            site_name: row.site_name,
+            favicon_url: row.favicon_url,
+            canonical_url: row.canonical_url,
+            content_type: row.content_type,
+            published_at: row
+                .published_at
+                .map(|published_at| published_at.timestamp()),
+            reading_time: row.reading_time.map(|reading_time| reading_time as u32),
+            embed: row.embed.map(|embed| embed.0).filter(oembed::is_trusted),
+        }
+    }
+}
+
+impl From<PreviewsRow> for CachedPreview {
+    fn from(row: PreviewsRow) -> Self {
+        let failures = row.failures as u32;
+        let expires_at = row.expires_at;
+        let preview = if row.failed {
+            None
+        } else {
+            Some(LinkPreview::from(row))
+        };
+
+        CachedPreview {
+            preview,
+            failures,
+            expires_at,
+        }
+    }
+}
+
+impl LinkPreviewService {
+    pub fn new(database_pool: Arc<PgPool>, images: Arc<ImageService>) -> Self {
+        LinkPreviewService {
+            database_pool,
+            images,
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Preview of `url`, `None` if the page couldn't be fetched. URLs the
+    /// server refuses to fetch are answered with `Error::ForbiddenUrl`.
+    pub async fn preview_from_url(&self, url: &str) -> Result<Option<LinkPreview>> {
+        let url_hash = sha256::hash(url);
+
+        match self.find_preview(&url_hash).await {
+            Ok(Some(cached)) => {
+                if cached.expires_at <= Utc::now() {
+                    self.refresh_in_background(url, &url_hash, cached.failures);
+                }
+
+                return Ok(cached.preview.map(|preview| self.proxy_images(preview)));
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("An error ocurred reading the link preview:\n{:?}", err),
+        }
+
+        Ok(self
+            .refresh(url, &url_hash, 0)
+            .await?
+            .map(|preview| self.proxy_images(preview)))
+    }
+
+    /// Previews of many URLs at once, stored previews are found with a
+    /// single query and the rest are fetched concurrently. URLs without a
+    /// preview, the ones refused included, are left out, as are the ones
+    /// still being fetched after `BATCH_TIMEOUT`. These are stored once
+    /// fetched, so the next batch finds them.
+    pub async fn previews_from_urls(&self, urls: &[String]) -> HashMap<String, LinkPreview> {
+        let mut urls = urls.to_vec();
+
+        urls.sort();
+        urls.dedup();
+
+        let mut previews = HashMap::with_capacity(urls.len());
+
+        if urls.is_empty() {
+            return previews;
+        }
+
+        let url_hashes: Vec<String> = urls.iter().map(|url| sha256::hash(url)).collect();
+        let mut cached = match self.find_previews(&url_hashes).await {
+            Ok(cached) => cached,
+            Err(err) => {
+                eprintln!("An error ocurred reading the link previews:\n{:?}", err);
+                HashMap::new()
+            }
+        };
+        let mut missing = Vec::new();
+
+        for (url, url_hash) in urls.iter().zip(url_hashes.iter()) {
+            match cached.remove(url_hash) {
+                Some(cached) => {
+                    if cached.expires_at <= Utc::now() {
+                        self.refresh_in_background(url, url_hash, cached.failures);
+                    }
+
+                    if let Some(preview) = cached.preview {
+                        previews.insert(url.clone(), self.proxy_images(preview));
+                    }
+                }
+                None => missing.push((url.clone(), url_hash.clone())),
+            }
+        }
+
+        if missing.is_empty() {
+            return previews;
+        }
+
+        let deadline = Instant::now() + BATCH_TIMEOUT;
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let service = self.clone();
+
+        // fetches outlive the batch when these take longer than its deadline
+        actix_web::rt::spawn(async move {
+            stream::iter(missing)
+                .for_each_concurrent(PREVIEW_FETCH_CONCURRENCY, |(url, url_hash)| {
+                    let service = &service;
+                    let sender = &sender;
+
+                    async move {
+                        let preview = service.refresh(&url, &url_hash, 0).await;
+
+                        // sending only fails once the batch was answered
+                        let _ = sender.send((url, preview));
+                    }
+                })
+                .await;
+        });
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            match timeout(remaining, receiver.recv()).await {
+                Ok(Some((url, Ok(Some(preview))))) => {
+                    previews.insert(url, self.proxy_images(preview));
+                }
+                Ok(Some(_)) => {}
+                // every URL was fetched, or the deadline passed
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        previews
+    }
+
+    /// Finds the stored preview of `url`, the page is not fetched if there
+    /// is none.
+    pub async fn find_stored_preview(&self, url: &str) -> Option<LinkPreview> {
+        self.find_preview(&sha256::hash(url))
+            .await
+            .ok()
+            .flatten()
+            .and_then(|cached| cached.preview)
+            .map(|preview| self.proxy_images(preview))
+    }
+
+    /// Purges previews beyond their retention for as long as the server
+    /// runs.
+    pub async fn run_purge(self) {
+        let mut interval = interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.purge_expired_previews().await {
+                eprintln!("An error ocurred purging link previews:\n{:?}", err);
+            }
+        }
+    }
+
+    /// Fetches the preview of `url` and stores it, failures are stored as
+    /// well along with the number of consecutive `failures`. URLs which are
+    /// refused aren't stored, so these keep being answered as such.
+    async fn refresh(
+        &self,
+        url: &str,
+        url_hash: &str,
+        failures: u32,
+    ) -> Result<Option<LinkPreview>> {
+        let (body, page_url) = match fetch_page(url).await {
+            Ok(page) => page,
+            Err(err @ Error::ForbiddenUrl(_)) | Err(err @ Error::BadRequest(_)) => return Err(err),
+            Err(_) => {
+                if let Err(err) = self.store_failure(url_hash, failures + 1).await {
+                    eprintln!("An error ocurred storing the link preview:\n{:?}", err);
+                }
+
+                return Ok(None);
+            }
+        };
+        let (mut link_preview, oembed_url) = parse_page(&body, &page_url);
+
+        if let Some(oembed_url) = oembed_url {
+            match fetch_oembed(&oembed_url).await {
+                Ok(oembed) => {
+                    if let Some(embed) = oembed.into_embed() {
+                        link_preview.embed = Some(embed);
+                    }
+                }
+                Err(err) => eprintln!("An error ocurred fetching the oEmbed:\n{:?}", err),
+            }
+        }
+
+        if let Err(err) = self.store_preview(url_hash, &link_preview).await {
+            eprintln!("An error ocurred storing the link preview:\n{:?}", err);
+
+            // the URL is left alone for a while rather than being fetched
+            // again on every request
+            if let Err(err) = self.store_failure(url_hash, failures + 1).await {
+                eprintln!("An error ocurred storing the link preview:\n{:?}", err);
+            }
+        }
+
+        Ok(Some(link_preview))
+    }
+
+    /// Replaces the image and the favicon of a preview with their proxied
+    /// URLs, so readers never load these from their hosts.
+    fn proxy_images(&self, mut preview: LinkPreview) -> LinkPreview {
+        preview.image_url = preview
+            .image_url
+            .map(|image_url| self.images.proxied_url(&image_url));
+        preview.favicon_url = preview
+            .favicon_url
+            .map(|favicon_url| self.images.proxied_url(&favicon_url));
+
+        preview
+    }
+
+    fn refresh_in_background(&self, url: &str, url_hash: &str, failures: u32) {
+        // a single refresh per URL is enough no matter how many requests
+        // find it expired
+        if !self.refreshing.lock().unwrap().insert(url_hash.to_string()) {
+            return;
+        }
+
+        let service = self.clone();
+        let url = url.to_string();
+        let url_hash = url_hash.to_string();
+
+        actix_web::rt::spawn(async move {
+            if let Err(err) = service.refresh(&url, &url_hash, failures).await {
+                eprintln!("An error ocurred refreshing the link preview:\n{:?}", err);
+            }
+
+            service.refreshing.lock().unwrap().remove(&url_hash);
+        });
+    }
+
+    async fn find_preview(&self, url_hash: &str) -> Result<Option<CachedPreview>> {
+        let row: Option<PreviewsRow> = query_as("SELECT * FROM previews WHERE url_hash = $1")
+            .bind(url_hash)
+            .fetch_optional(&*self.database_pool)
+            .await?;
+
+        Ok(row.map(CachedPreview::from))
+    }
+
+    async fn find_previews(&self, url_hashes: &[String]) -> Result<HashMap<String, CachedPreview>> {
+        let rows: Vec<PreviewsRow> = query_as("SELECT * FROM previews WHERE url_hash = ANY($1)")
+            .bind(url_hashes)
+            .fetch_all(&*self.database_pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.url_hash.clone(), CachedPreview::from(row)))
+            .collect())
+    }
+
+    async fn store_preview(&self, url_hash: &str, link_preview: &LinkPreview) -> Result<()> {
+        let published_at = link_preview
+            .published_at
+            .map(|published_at| Utc.timestamp(published_at, 0));
+
+        query(
+            r#"
+        INSERT INTO previews (
+            title,
+            description,
+            domain,
+            url_hash,
+            image_url,
+            site_name,
+            favicon_url,
+            canonical_url,
+            content_type,
+            published_at,
+            reading_time,
+            embed,
+            expires_at
+        ) VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8,
+            $9,
+            $10,
+            $11,
+            $12,
+            $13
+        ) ON CONFLICT (url_hash) DO UPDATE SET
+            title = EXCLUDED.title,
+            description = EXCLUDED.description,
+            domain = EXCLUDED.domain,
+            image_url = EXCLUDED.image_url,
+            site_name = EXCLUDED.site_name,
+            favicon_url = EXCLUDED.favicon_url,
+            canonical_url = EXCLUDED.canonical_url,
+            content_type = EXCLUDED.content_type,
+            published_at = EXCLUDED.published_at,
+            reading_time = EXCLUDED.reading_time,
+            embed = EXCLUDED.embed,
+            expires_at = EXCLUDED.expires_at,
+            failed = FALSE,
+            failures = 0,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(link_preview.title.clone())
+        .bind(link_preview.description.clone())
+        .bind(link_preview.domain.clone())
+        .bind(url_hash)
+        .bind(link_preview.image_url.clone())
+        .bind(link_preview.site_name.clone())
+        .bind(link_preview.favicon_url.clone())
+        .bind(link_preview.canonical_url.clone())
+        .bind(link_preview.content_type.clone())
+        .bind(published_at)
+        .bind(
+            link_preview
+                .reading_time
+                .map(|reading_time| reading_time as i32),
+        )
+        .bind(link_preview.embed.clone().map(Json))
+        .bind(expires_in(PREVIEW_TTL))
+        .execute(&*self.database_pool)
+        .await?;
+
+        for image_url in link_preview
+            .image_url
+            .iter()
+            .chain(link_preview.favicon_url.iter())
+        {
+            self.images.register(image_url).await?;
+        }
+
+        Ok(())
+    }
+
+    /// A previously fetched preview is kept as it is, so it's still served
+    /// until the URL is tried again.
+    async fn store_failure(&self, url_hash: &str, failures: u32) -> Result<()> {
+        query(
+            r#"
+        INSERT INTO previews (
+            url_hash,
+            expires_at,
+            failed,
+            failures
+        ) VALUES (
+            $1,
+            $2,
+            TRUE,
+            $3
+        ) ON CONFLICT (url_hash) DO UPDATE SET
+            expires_at = EXCLUDED.expires_at,
+            failures = EXCLUDED.failures,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(url_hash)
+        .bind(expires_in(failure_backoff(failures)))
+        .bind(failures as i32)
+        .execute(&*self.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge_expired_previews(&self) -> Result<()> {
+        query("DELETE FROM previews WHERE expires_at < $1")
+            .bind(Utc::now() - chrono::Duration::from_std(PREVIEW_RETENTION).unwrap())
+            .execute(&*self.database_pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Preview of the page at `url` along with the oEmbed endpoint describing
+/// it, if any.
+fn parse_page(body: &str, url: &Url) -> (LinkPreview, Option<Url>) {
+    let html = Html::parse_document(body);
+    let found = link_preview::LinkPreview::from(&html);
+    let metadata = metadata::parse(&html, url);
+    let link_preview = LinkPreview {
+        title: found.title,
+        image_url: found.image_url.map(|image_url| image_url.to_string()),
+        // descriptions could hold HTML tags, these are removed
+        description: found
+            .description
+            .map(|description| remove_html_tags(description.as_str())),
+        domain: found.domain.or_else(|| url.host_str().map(String::from)),
+        site_name: metadata.site_name,
+        favicon_url: metadata.favicon_url,
+        canonical_url: metadata.canonical_url,
+        content_type: metadata.content_type,
+        published_at: metadata
+            .published_at
+            .map(|published_at| published_at.timestamp()),
+        reading_time: metadata.reading_time,
+        embed: metadata.player,
+    };
+    let oembed_url = metadata.oembed_url.or_else(|| oembed::endpoint(url));
+
+    (link_preview, oembed_url)
+}
+
+fn expires_in(ttl: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(ttl).unwrap()
+}
+
+/// Time before trying again a URL which failed to be fetched `failures`
+/// times in a row, images which fail are tried again with the same backoff.
+pub fn failure_backoff(failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+
+    FAILURE_BACKOFF
+        .checked_mul(factor)
+        .unwrap_or(MAX_FAILURE_BACKOFF)
+        .min(MAX_FAILURE_BACKOFF)
+}
+
+#[cfg(test)]
+mod test {
+    use common::LinkPreview;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::services::images::ImageService;
+    use crate::testing::database_pool;
+    use crate::utils::sha256;
+
+    use super::{failure_backoff, LinkPreviewService, FAILURE_BACKOFF, MAX_FAILURE_BACKOFF};
+
+    #[actix_rt::test]
+    async fn stores_previews_with_long_fields() {
+        let database_pool = match database_pool().await {
+            Some(database_pool) => Arc::new(database_pool),
+            None => return,
+        };
+        let images = Arc::new(ImageService::new(
+            Arc::clone(&database_pool),
+            "http://0.0.0.0:3000",
+        ));
+        let service = LinkPreviewService::new(database_pool, images);
+        let url_hash = sha256::hash("https://example.com/a-page-with-a-long-description");
+        let link_preview = LinkPreview {
+            title: Some("A".repeat(300)),
+            description: Some("B".repeat(1000)),
+            domain: Some(format!("{}.example.com", "c".repeat(250))),
+            image_url: Some(format!("https://cdn.example.com/{}.png", "d".repeat(400))),
+            ..LinkPreview::default()
+        };
+
+        service
+            .store_preview(&url_hash, &link_preview)
+            .await
+            .unwrap();
+
+        let cached = service.find_preview(&url_hash).await.unwrap().unwrap();
+
+        assert_eq!(cached.preview, Some(link_preview));
+        assert_eq!(cached.failures, 0);
+    }
+
+    #[test]
+    fn doubles_the_backoff_on_every_failure() {
+        assert_eq!(failure_backoff(1), FAILURE_BACKOFF);
+        assert_eq!(failure_backoff(2), FAILURE_BACKOFF * 2);
+        assert_eq!(failure_backoff(4), Duration::from_secs(80 * 60));
+    }
+
+    #[test]
+    fn caps_the_backoff_for_urls_failing_for_long() {
+        assert_eq!(failure_backoff(9), MAX_FAILURE_BACKOFF);
+        assert_eq!(failure_backoff(100), MAX_FAILURE_BACKOFF);
+    }
+}
@@ -0,0 +1,254 @@
+//! https://oembed.com
+//!
+//! Video and tweet links are described by their providers through oEmbed,
+//! only players and the text of posts are kept out of the HTML these answer
+//! with, so no markup from a provider ever reaches readers.
+
+use common::{Embed, EmbedKind};
+use reqwest::Url;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+use super::fetch::{fetch, read_body};
+
+/// Largest oEmbed response read, in bytes.
+const MAX_OEMBED_SIZE: usize = 64 * 1024;
+
+/// oEmbed endpoints of providers by domain, for pages which don't advertise
+/// theirs. Subdomains of these domains are served by the same endpoint.
+const PROVIDERS: &[(&str, &str)] = &[
+    ("youtube.com", "https://www.youtube.com/oembed"),
+    ("youtu.be", "https://www.youtube.com/oembed"),
+    ("vimeo.com", "https://vimeo.com/api/oembed.json"),
+    ("twitter.com", "https://publish.twitter.com/oembed"),
+    ("x.com", "https://publish.twitter.com/oembed"),
+];
+
+#[derive(Debug, Deserialize)]
+pub struct OEmbed {
+    #[serde(rename = "type")]
+    kind: String,
+    provider_name: Option<String>,
+    author_name: Option<String>,
+    html: Option<String>,
+    // providers answer with either numbers or strings
+    width: Option<Value>,
+    height: Option<Value>,
+}
+
+impl OEmbed {
+    /// Embed described by the oEmbed response, only videos with a player
+    /// and posts with text are embedded.
+    pub fn into_embed(self) -> Option<Embed> {
+        let html = Html::parse_fragment(self.html.as_deref()?);
+        let (kind, player_url, text) = match self.kind.as_str() {
+            "video" => {
+                let iframe = Selector::parse("iframe[src]").ok()?;
+                let player_url = html
+                    .select(&iframe)
+                    .filter_map(|element| element.value().attr("src"))
+                    .find_map(|src| Url::parse(src).ok())
+                    .filter(is_known_player)?;
+
+                (EmbedKind::Video, Some(String::from(player_url)), None)
+            }
+            "rich" => {
+                let paragraph = Selector::parse("blockquote p").ok()?;
+                let text = html
+                    .select(&paragraph)
+                    .map(|element| element.text().collect::<String>())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                let text = text.trim();
+
+                if text.is_empty() {
+                    return None;
+                }
+
+                (EmbedKind::Post, None, Some(String::from(text)))
+            }
+            _ => return None,
+        };
+
+        Some(Embed {
+            kind,
+            provider_name: self.provider_name,
+            author_name: self.author_name,
+            player_url,
+            text,
+            width: dimension(self.width.as_ref()),
+            height: dimension(self.height.as_ref()),
+        })
+    }
+}
+
+/// oEmbed endpoint of the provider of `url`, if it's a known one.
+pub fn endpoint(url: &Url) -> Option<Url> {
+    let endpoint = provider_endpoint(url)?;
+
+    Url::parse_with_params(endpoint, &[("url", url.as_str()), ("format", "json")]).ok()
+}
+
+/// Whether the player at `url` is served by one of the known providers.
+/// Players run scripts in the stream of every reader, so these are only
+/// embedded from the `PROVIDERS` as any page can point its own anywhere.
+pub fn is_known_player(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https") && provider_endpoint(url).is_some()
+}
+
+/// Whether `embed` can be rendered, posts are only text while players must
+/// be known ones. Embeds stored before players were checked may not be.
+pub fn is_trusted(embed: &Embed) -> bool {
+    match embed.player_url.as_deref() {
+        Some(player_url) => Url::parse(player_url)
+            .map(|url| is_known_player(&url))
+            .unwrap_or(false),
+        None => true,
+    }
+}
+
+fn provider_endpoint(url: &Url) -> Option<&'static str> {
+    let host = url.host_str()?;
+
+    PROVIDERS
+        .iter()
+        .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+        .map(|(_, endpoint)| *endpoint)
+}
+
+/// Fetches the oEmbed response at `endpoint`, endpoints are fetched as any
+/// other URL would be.
+pub async fn fetch_oembed(endpoint: &Url) -> Result<OEmbed> {
+    let body = read_body(fetch(endpoint.as_str()).await?, MAX_OEMBED_SIZE).await?;
+
+    serde_json::from_slice(&body)
+        .map_err(|err| Error::ExternalUnavailable(format!("Invalid oEmbed response: {}", err)))
+}
+
+fn dimension(value: Option<&Value>) -> Option<u32> {
+    match value? {
+        Value::Number(number) => number.as_u64().map(|number| number as u32),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{Embed, EmbedKind};
+    use reqwest::Url;
+
+    use super::{endpoint, is_trusted, OEmbed};
+
+    #[test]
+    fn finds_endpoints_of_known_providers() {
+        let url = Url::parse("https://m.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+
+        assert_eq!(
+            endpoint(&url).unwrap().as_str(),
+            "https://www.youtube.com/oembed?url=https%3A%2F%2Fm.youtube.com%2Fwatch%3Fv%3DdQw4w9WgXcQ&format=json"
+        );
+        assert!(endpoint(&Url::parse("https://x.com/rustlang/status/1").unwrap()).is_some());
+        assert!(endpoint(&Url::parse("https://notyoutube.com/watch").unwrap()).is_none());
+        assert!(endpoint(&Url::parse("https://example.com/").unwrap()).is_none());
+    }
+
+    #[test]
+    fn embeds_the_player_of_videos() {
+        let oembed: OEmbed = serde_json::from_str(
+            r#"{
+                "type": "video",
+                "provider_name": "YouTube",
+                "author_name": "Rust",
+                "width": 200,
+                "height": "113",
+                "html": "<iframe width=\"200\" height=\"113\" src=\"https://www.youtube.com/embed/dQw4w9WgXcQ?feature=oembed\" allowfullscreen></iframe>"
+            }"#,
+        )
+        .unwrap();
+        let embed = oembed.into_embed().unwrap();
+
+        assert_eq!(embed.kind, EmbedKind::Video);
+        assert_eq!(
+            embed.player_url.as_deref(),
+            Some("https://www.youtube.com/embed/dQw4w9WgXcQ?feature=oembed")
+        );
+        assert_eq!((embed.width, embed.height), (Some(200), Some(113)));
+        assert_eq!(embed.text, None);
+    }
+
+    #[test]
+    fn embeds_the_text_of_posts() {
+        let oembed: OEmbed = serde_json::from_str(
+            r#"{
+                "type": "rich",
+                "provider_name": "Twitter",
+                "author_name": "Rust Language",
+                "width": 550,
+                "height": null,
+                "html": "<blockquote class=\"twitter-tweet\"><p lang=\"en\" dir=\"ltr\">Rust 1.56 is out <a href=\"https://t.co/x\">https://t.co/x</a></p>&mdash; Rust Language (@rustlang) <a href=\"https://twitter.com/rustlang/status/1\">October 21, 2021</a></blockquote>\n<script async src=\"https://platform.twitter.com/widgets.js\"></script>"
+            }"#,
+        )
+        .unwrap();
+        let embed = oembed.into_embed().unwrap();
+
+        assert_eq!(embed.kind, EmbedKind::Post);
+        assert_eq!(
+            embed.text.as_deref(),
+            Some("Rust 1.56 is out https://t.co/x")
+        );
+        assert_eq!(embed.author_name.as_deref(), Some("Rust Language"));
+        assert_eq!(embed.player_url, None);
+    }
+
+    #[test]
+    fn leaves_players_of_unknown_hosts_out() {
+        let oembed: OEmbed = serde_json::from_str(
+            r#"{
+                "type": "video",
+                "html": "<iframe src=\"https://tracker.example.com/player\"></iframe>"
+            }"#,
+        )
+        .unwrap();
+        let embed = oembed.into_embed();
+
+        assert!(embed.is_none());
+    }
+
+    #[test]
+    fn trusts_posts_and_players_of_known_providers() {
+        let embed = |player_url: Option<&str>| Embed {
+            kind: if player_url.is_some() {
+                EmbedKind::Video
+            } else {
+                EmbedKind::Post
+            },
+            provider_name: None,
+            author_name: None,
+            player_url: player_url.map(String::from),
+            text: None,
+            width: None,
+            height: None,
+        };
+
+        assert!(is_trusted(&embed(None)));
+        assert!(is_trusted(&embed(Some("https://player.vimeo.com/video/1"))));
+        assert!(!is_trusted(&embed(Some(
+            "https://tracker.example.com/player"
+        ))));
+        assert!(!is_trusted(&embed(Some("javascript:alert(1)"))));
+    }
+
+    #[test]
+    fn leaves_other_kinds_of_media_out() {
+        let oembed: OEmbed = serde_json::from_str(
+            r#"{ "type": "photo", "url": "https://example.com/photo.jpg", "width": 1, "height": 1 }"#,
+        )
+        .unwrap();
+
+        assert!(oembed.into_embed().is_none());
+    }
+}
@@ -1,2 +1,7 @@
+pub mod feeds;
 pub mod hacker_news;
+pub mod images;
 pub mod link_preview;
+pub mod pages;
+pub mod reader;
+pub mod search;
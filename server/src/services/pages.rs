@@ -0,0 +1,328 @@
+//! Server rendering of the pages crawlers and link unfurlers land on. Pages
+//! are rendered from `common::views`, the same views the client components
+//! render, into the `index.html` built by trunk along with the data these
+//! were rendered with. The client replaces it with its own components once
+//! the bundle is loaded, starting from the embedded data instead of
+//! fetching it again. Story pages carry their own title, Open Graph and
+//! Twitter card tags, so links to these unfurl with the story in chats.
+
+use common::hacker_news::Story;
+use common::html::escape;
+use common::views::{self, StoryCard};
+use common::{InitialData, LinkPreview, Page, StoryPage};
+use yew::prelude::*;
+
+use crate::utils::view;
+
+/// `index.html` of the client bundle, pages are rendered into its body.
+pub struct PageTemplate {
+    html: String,
+}
+
+impl PageTemplate {
+    pub fn new(html: String) -> Self {
+        PageTemplate { html }
+    }
+
+    /// The bundle as it is, for pages the server doesn't render.
+    pub fn fallback(&self) -> &str {
+        self.html.as_str()
+    }
+
+    /// Renders the stream of stories of `feed`.
+    pub fn render_stream(&self, feed: &str, page: Page<Story>) -> String {
+        // previews are fetched by the client
+        let content = html! {
+            <div id="stream-wrapper">
+                <ul id="stream">
+                    {
+                        for page.items.iter().map(|story| {
+                            let card = StoryCard {
+                                story,
+                                preview: None,
+                                is_loading_preview: false,
+                            };
+
+                            views::story_card(&card, views::anchor)
+                        })
+                    }
+                </ul>
+            </div>
+        };
+
+        self.render(
+            None,
+            &content,
+            &InitialData::Stream {
+                feed: feed.to_string(),
+                page: Box::new(page),
+            },
+        )
+    }
+
+    /// Renders the page of `story` found at `page_url`, its image is the one
+    /// of the story's link preview.
+    pub fn render_story(
+        &self,
+        story: Story,
+        link_preview: Option<LinkPreview>,
+        page_url: &str,
+    ) -> String {
+        let image_url = link_preview
+            .as_ref()
+            .and_then(|link_preview| link_preview.image_url.as_deref());
+        // the proxy serves larger thumbnails for the top of the page
+        let head = story_head(
+            &story,
+            image_url
+                .map(|image_url| format!("{}?size=hero", image_url))
+                .as_deref(),
+            page_url,
+        );
+        let content = html! {
+            <section id="story-page">
+                { views::story_page_header(story.id, views::anchor) }
+                <main class="story-content">
+                    { views::story_heading(&story.title, image_url) }
+                    <hr />
+                </main>
+            </section>
+        };
+
+        self.render(
+            Some(&head),
+            &content,
+            &InitialData::Story(Box::new(StoryPage {
+                story,
+                link_preview,
+            })),
+        )
+    }
+
+    /// Writes `content` and the data it was rendered with right after the
+    /// opening `body` tag, the bundle's scripts follow them. The title of
+    /// the bundle is replaced by the one in `head`, if any.
+    fn render(&self, head: Option<&str>, content: &Html, initial_data: &InitialData) -> String {
+        let html = match head {
+            Some(head) => with_head(&self.html, head),
+            None => self.html.clone(),
+        };
+        let body_start = match html.find("<body") {
+            Some(body) => match html[body..].find('>') {
+                Some(end) => body + end + 1,
+                None => return html,
+            },
+            None => return html,
+        };
+        // data is embedded in a script, so it must not close it
+        let initial_data = serde_json::to_string(initial_data)
+            .unwrap_or_default()
+            .replace('<', "\\u003c");
+
+        format!(
+            r#"{}<div id="ssr">{}<main id="app-main">{}</main></div><script id="initial-data" type="application/json">{}</script>{}"#,
+            &html[..body_start],
+            view::render(&render_header()),
+            view::render(content),
+            initial_data,
+            &html[body_start..]
+        )
+    }
+}
+
+/// Removes the title of `html` and writes `head` at the end of its `head`
+/// element.
+fn with_head(html: &str, head: &str) -> String {
+    let html = match (html.find("<title>"), html.find("</title>")) {
+        (Some(start), Some(end)) if start < end => {
+            format!("{}{}", &html[..start], &html[end + "</title>".len()..])
+        }
+        _ => html.to_string(),
+    };
+
+    match html.find("</head>") {
+        Some(end) => format!("{}{}{}", &html[..end], head, &html[end..]),
+        None => html,
+    }
+}
+
+/// Title, Open Graph and Twitter card tags of the page of `story`, links
+/// to it unfurl with its title, score, comments and image.
+fn story_head(story: &Story, image_url: Option<&str>, page_url: &str) -> String {
+    let title = escape(&story.title);
+    let description = escape(&format!(
+        "{} by {} | {}",
+        count(story.score as u64, "point"),
+        story.by,
+        count(story.descendants.unwrap_or(0), "comment")
+    ));
+    let card = if image_url.is_some() {
+        "summary_large_image"
+    } else {
+        "summary"
+    };
+    let mut head = format!(
+        concat!(
+            "<title>{title} | FluxCap</title>",
+            r#"<meta property="og:type" content="article" />"#,
+            r#"<meta property="og:site_name" content="FluxCap" />"#,
+            r#"<meta property="og:title" content="{title}" />"#,
+            r#"<meta property="og:description" content="{description}" />"#,
+            r#"<meta property="og:url" content="{url}" />"#,
+            r#"<meta name="twitter:card" content="{card}" />"#,
+            r#"<meta name="twitter:title" content="{title}" />"#,
+            r#"<meta name="twitter:description" content="{description}" />"#,
+        ),
+        title = title,
+        description = description,
+        url = escape(page_url),
+        card = card,
+    );
+
+    if let Some(image_url) = image_url {
+        head.push_str(&format!(
+            r#"<meta property="og:image" content="{image}" /><meta name="twitter:image" content="{image}" />"#,
+            image = escape(image_url)
+        ));
+    }
+
+    head
+}
+
+fn count(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Header of the app, without search as it needs the client.
+fn render_header() -> Html {
+    html! {
+        <header id="app-header">
+            { views::header_branding() }
+            { views::feed_tabs(views::anchor) }
+        </header>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::hacker_news::{Story, Type};
+    use common::{InitialData, LinkPreview, Page, StoryPage};
+
+    use super::PageTemplate;
+
+    const INDEX_HTML: &str = r#"<!DOCTYPE html><html><head><title>FluxCap</title></head><body><script src="/client.js"></script></body></html>"#;
+
+    fn story(title: &str) -> Story {
+        Story {
+            id: 8863,
+            r#type: Type::Story,
+            by: String::from("dhouston"),
+            time: 1175714200,
+            kids: Some(vec![9224, 8917]),
+            url: Some(String::from(
+                "http://www.getdropbox.com/u/2/screencast.html",
+            )),
+            score: 111,
+            title: String::from(title),
+            descendants: Some(71),
+        }
+    }
+
+    fn initial_data(html: &str) -> InitialData {
+        let start = html.find(r#"type="application/json">"#).unwrap() + 24;
+        let end = start + html[start..].find("</script>").unwrap();
+
+        serde_json::from_str(&html[start..end]).unwrap()
+    }
+
+    #[test]
+    fn renders_stories_before_the_bundle() {
+        let template = PageTemplate::new(String::from(INDEX_HTML));
+        let page = Page {
+            items: vec![story("My YC app: Dropbox")],
+            next_cursor: None,
+            has_more: false,
+        };
+        let html = template.render_stream("new", page.clone());
+
+        assert!(html.starts_with(
+            "<!DOCTYPE html><html><head><title>FluxCap</title></head><body><div id=\"ssr\">"
+        ));
+        assert!(html.ends_with(r#"</script><script src="/client.js"></script></body></html>"#));
+        assert!(html.contains(r#"<h2 class="story-title">My YC app: Dropbox</h2>"#));
+        assert!(html.contains(r#"<a href="/users/dhouston" class="author-link">dhouston</a>"#));
+        assert!(html.contains("2007-04-04 at 19:16"));
+        assert_eq!(
+            initial_data(&html),
+            InitialData::Stream {
+                feed: String::from("new"),
+                page: Box::new(page)
+            }
+        );
+    }
+
+    #[test]
+    fn escapes_text_and_embedded_data() {
+        let template = PageTemplate::new(String::from(INDEX_HTML));
+        let story = story("</script><script>alert(1)</script>");
+        let html =
+            template.render_story(story.clone(), None, "https://fluxcap.example/stories/8863");
+
+        assert_eq!(html.matches("<script").count(), 2);
+        assert!(html.contains("&lt;/script&gt;&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert_eq!(
+            initial_data(&html),
+            InitialData::Story(Box::new(StoryPage {
+                story,
+                link_preview: None
+            }))
+        );
+    }
+
+    #[test]
+    fn leaves_templates_without_a_body_as_these_are() {
+        let template = PageTemplate::new(String::from("<html></html>"));
+
+        assert_eq!(
+            template.render_story(
+                story("Dropbox"),
+                None,
+                "https://fluxcap.example/stories/8863"
+            ),
+            "<html></html>"
+        );
+    }
+
+    #[test]
+    fn describes_stories_for_link_unfurlers() {
+        let template = PageTemplate::new(String::from(INDEX_HTML));
+        let link_preview = LinkPreview {
+            image_url: Some(String::from("https://fluxcap.example/api/v1/images/ABCDEF")),
+            ..LinkPreview::default()
+        };
+        let html = template.render_story(
+            story("My YC app: Dropbox"),
+            Some(link_preview),
+            "https://fluxcap.example/stories/8863",
+        );
+        let head = &html[..html.find("</head>").unwrap()];
+
+        assert_eq!(html.matches("<title>").count(), 1);
+        assert!(head.contains("<title>My YC app: Dropbox | FluxCap</title>"));
+        assert!(head.contains(r#"<meta property="og:title" content="My YC app: Dropbox" />"#));
+        assert!(head.contains(
+            r#"<meta property="og:description" content="111 points by dhouston | 71 comments" />"#
+        ));
+        assert!(head.contains(
+            r#"<meta property="og:url" content="https://fluxcap.example/stories/8863" />"#
+        ));
+        assert!(head.contains(
+            r#"<meta property="og:image" content="https://fluxcap.example/api/v1/images/ABCDEF?size=hero" />"#
+        ));
+        assert!(head.contains(r#"<meta name="twitter:card" content="summary_large_image" />"#));
+    }
+}
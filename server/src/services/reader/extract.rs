@@ -0,0 +1,425 @@
+//! Extraction of the main article of a page, in the spirit of Readability.
+//! Paragraphs score the elements holding them and the highest scoring one
+//! is taken as the article, unless the page marks its article up. The
+//! article is written back out with an allow-list of elements and
+//! attributes, other elements are either unwrapped or dropped along with
+//! their content.
+
+use common::html::escape;
+use reqwest::Url;
+use scraper::node::Node;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+/// Shortest text taken as an article, in characters.
+const MIN_ARTICLE_LENGTH: usize = 250;
+
+/// Shortest paragraph which counts towards the score of its parents.
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// Elements written out as they are, `h1` is written as `h2` as the title
+/// of the article is shown apart.
+const ALLOWED_ELEMENTS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Elements dropped along with their content.
+const DROPPED_ELEMENTS: &[&str] = &[
+    "aside", "button", "canvas", "embed", "footer", "form", "header", "iframe", "input", "nav",
+    "noscript", "object", "script", "select", "style", "svg", "template", "textarea", "video",
+];
+
+/// Elements ending a paragraph of the plain text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "blockquote",
+    "div",
+    "figcaption",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "li",
+    "p",
+    "pre",
+    "section",
+    "tr",
+];
+
+/// Classes and ids of elements which are unlikely to be part of an article.
+const UNLIKELY_NAMES: &[&str] = &[
+    "advert",
+    "banner",
+    "comment",
+    "cookie",
+    "footer",
+    "menu",
+    "newsletter",
+    "promo",
+    "related",
+    "share",
+    "sidebar",
+    "social",
+    "subscribe",
+];
+
+#[derive(Debug, PartialEq)]
+pub struct Extracted {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    /// Sanitized HTML of the article
+    pub html: String,
+    /// Plain text of the article, paragraphs are separated by blank lines
+    pub text: String,
+    /// Original URLs of the images in the article
+    pub images: Vec<String>,
+}
+
+/// Extracts the article of the page fetched from `url`, `None` if there's
+/// no article in it. Image sources are replaced by `proxy_image`.
+pub fn extract(body: &str, url: &Url, proxy_image: &dyn Fn(&str) -> String) -> Option<Extracted> {
+    let html = Html::parse_document(body);
+    let article = find_article(&html)?;
+    let mut writer = Writer {
+        url,
+        proxy_image,
+        html: String::new(),
+        text: String::new(),
+        images: Vec::new(),
+    };
+
+    writer.write_children(article);
+
+    let text = writer
+        .text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join("\n\n");
+
+    if text.chars().count() < MIN_ARTICLE_LENGTH {
+        return None;
+    }
+
+    Some(Extracted {
+        title: meta(&html, "og:title").or_else(|| first_text(&html, "title")),
+        byline: meta(&html, "author")
+            .or_else(|| first_text(&html, "[rel=\"author\"]"))
+            .or_else(|| first_text(&html, "[itemprop=\"author\"]")),
+        html: writer.html,
+        text,
+        images: writer.images,
+    })
+}
+
+fn find_article(html: &Html) -> Option<ElementRef<'_>> {
+    // articles which are marked up as such are trusted
+    let marked = Selector::parse("[itemprop=\"articleBody\"], article").ok()?;
+
+    if let Some(article) = html
+        .select(&marked)
+        .max_by_key(|element| text_length(*element))
+        .filter(|element| text_length(*element) >= MIN_ARTICLE_LENGTH)
+    {
+        return Some(article);
+    }
+
+    let paragraphs = Selector::parse("p").ok()?;
+    let mut scores = HashMap::new();
+
+    for paragraph in html.select(&paragraphs) {
+        let text = paragraph.text().collect::<String>();
+        let length = text.trim().chars().count();
+
+        if length < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+
+        // longer paragraphs with more clauses are more likely to be prose
+        let score = 1.0 + text.matches(',').count() as f64 + (length / 100).min(3) as f64;
+        let parent = paragraph.parent().and_then(ElementRef::wrap);
+        let grandparent = parent
+            .and_then(|parent| parent.parent())
+            .and_then(ElementRef::wrap);
+
+        // grandparents get half of the score, as these could be holding
+        // much more than the article
+        for (candidate, score) in [(parent, score), (grandparent, score / 2.0)] {
+            if let Some(candidate) = candidate.filter(|candidate| !is_unlikely(*candidate)) {
+                scores.entry(candidate.id()).or_insert((candidate, 0.0)).1 += score;
+            }
+        }
+    }
+
+    scores
+        .into_iter()
+        .map(|(_, (candidate, score))| (candidate, score * (1.0 - link_density(candidate))))
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(candidate, _)| candidate)
+}
+
+fn text_length(element: ElementRef) -> usize {
+    element.text().map(|text| text.trim().chars().count()).sum()
+}
+
+/// Share of the text of `element` which is in links, navigation is mostly
+/// made of links while prose isn't.
+fn link_density(element: ElementRef) -> f64 {
+    let length = text_length(element);
+
+    if length == 0 {
+        return 1.0;
+    }
+
+    let links = Selector::parse("a").unwrap();
+    let link_length: usize = element.select(&links).map(text_length).sum();
+
+    link_length as f64 / length as f64
+}
+
+fn is_unlikely(element: ElementRef) -> bool {
+    let names = format!(
+        "{} {}",
+        element.value().attr("class").unwrap_or_default(),
+        element.value().attr("id").unwrap_or_default()
+    )
+    .to_lowercase();
+
+    UNLIKELY_NAMES.iter().any(|name| names.contains(name))
+}
+
+fn meta(html: &Html, name: &str) -> Option<String> {
+    let selector = Selector::parse(&format!(
+        "meta[property=\"{}\"], meta[name=\"{}\"]",
+        name, name
+    ))
+    .ok()?;
+
+    html.select(&selector)
+        .filter_map(|element| element.value().attr("content"))
+        .map(str::trim)
+        .find(|content| !content.is_empty())
+        .map(String::from)
+}
+
+fn first_text(html: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+
+    html.select(&selector)
+        .map(|element| element.text().collect::<String>().trim().to_string())
+        .find(|text| !text.is_empty())
+}
+
+/// Writes the allowed elements of an article as HTML along with its plain
+/// text.
+struct Writer<'a> {
+    url: &'a Url,
+    proxy_image: &'a dyn Fn(&str) -> String,
+    html: String,
+    text: String,
+    images: Vec<String>,
+}
+
+impl<'a> Writer<'a> {
+    fn write_children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    self.html.push_str(&escape(text));
+                    self.text.push_str(text);
+                }
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.write_element(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn write_element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+
+        if DROPPED_ELEMENTS.contains(&name) || is_unlikely(element) {
+            return;
+        }
+
+        if !ALLOWED_ELEMENTS.contains(&name) {
+            self.write_children(element);
+        } else if name == "img" {
+            self.write_image(element);
+        } else if name == "br" || name == "hr" {
+            self.html.push_str(&format!("<{}>", name));
+        } else {
+            let name = if name == "h1" { "h2" } else { name };
+
+            self.html.push('<');
+            self.html.push_str(name);
+
+            if name == "a" {
+                if let Some(href) = element
+                    .value()
+                    .attr("href")
+                    .and_then(|href| absolute(self.url, href))
+                {
+                    self.html.push_str(&format!(
+                        r#" href="{}" rel="nofollow noopener" target="_blank""#,
+                        escape(&href)
+                    ));
+                }
+            }
+
+            self.html.push('>');
+            self.write_children(element);
+            self.html.push_str(&format!("</{}>", name));
+        }
+
+        if BLOCK_ELEMENTS.contains(&name) || name == "br" {
+            self.text.push('\n');
+        }
+    }
+
+    fn write_image(&mut self, element: ElementRef) {
+        // lazily loaded images keep their source aside
+        let src = element
+            .value()
+            .attr("src")
+            .or_else(|| element.value().attr("data-src"))
+            .and_then(|src| absolute(self.url, src));
+
+        if let Some(src) = src {
+            self.html.push_str(&format!(
+                r#"<img src="{}" alt="{}">"#,
+                escape(&(self.proxy_image)(&src)),
+                escape(element.value().attr("alt").unwrap_or_default())
+            ));
+            self.images.push(src);
+        }
+    }
+}
+
+/// Resolves `href` against `url`, only http and https URLs are kept.
+fn absolute(url: &Url, href: &str) -> Option<String> {
+    url.join(href)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::Url;
+
+    use super::{extract, Extracted};
+
+    const PROSE: &str = "Rust is a multi-paradigm, general-purpose programming language designed for performance and safety, especially safe concurrency. Rust is syntactically similar to C++, but can guarantee memory safety by using a borrow checker to validate references.";
+
+    fn extract_page(body: &str) -> Option<Extracted> {
+        let url = Url::parse("https://blog.example.com/posts/rust").unwrap();
+
+        extract(body, &url, &|src| format!("/proxy?url={}", src))
+    }
+
+    #[test]
+    fn extracts_the_highest_scoring_element() {
+        let extracted = extract_page(&format!(
+            r#"
+            <html>
+                <head>
+                    <title>Rust</title>
+                    <meta name="author" content="Ferris">
+                </head>
+                <body>
+                    <div class="menu"><p>Home, Blog, About, Contact, Subscribe to our newsletter</p></div>
+                    <div id="content">
+                        <h1>Rust</h1>
+                        <p>{}</p>
+                        <p>{}</p>
+                    </div>
+                    <div class="sidebar-comments"><p>3 comments</p></div>
+                </body>
+            </html>
+            "#,
+            PROSE, PROSE
+        ))
+        .unwrap();
+
+        assert_eq!(extracted.title.as_deref(), Some("Rust"));
+        assert_eq!(extracted.byline.as_deref(), Some("Ferris"));
+        assert!(extracted.html.contains("<h2>Rust</h2>"));
+        assert_eq!(extracted.html.matches("<p>").count(), 2);
+        assert_eq!(extracted.text, format!("Rust\n\n{}\n\n{}", PROSE, PROSE));
+    }
+
+    #[test]
+    fn writes_out_allowed_elements_only() {
+        let extracted = extract_page(&format!(
+            r#"
+            <article>
+                <p onclick="steal()" style="color: red">{} <a href="/docs" onmouseover="steal()">Docs</a> <a href="javascript:steal()">Click</a></p>
+                <script>steal()</script>
+                <iframe src="https://evil.example.com"></iframe>
+                <p><img src="/ferris.png" alt="Ferris &quot;the crab&quot;" onerror="steal()"><span>&lt;script&gt;</span></p>
+            </article>
+            "#,
+            PROSE
+        ))
+        .unwrap();
+
+        assert!(!extracted.html.contains("steal"));
+        assert!(!extracted.html.contains("evil"));
+        assert!(extracted.html.contains(
+            r#"<a href="https://blog.example.com/docs" rel="nofollow noopener" target="_blank">Docs</a>"#
+        ));
+        assert!(extracted.html.contains("<a>Click</a>"));
+        assert!(extracted.html.contains(
+            r#"<img src="/proxy?url=https://blog.example.com/ferris.png" alt="Ferris &quot;the crab&quot;">"#
+        ));
+        assert!(extracted.html.contains("&lt;script&gt;"));
+        assert_eq!(
+            extracted.images,
+            vec!["https://blog.example.com/ferris.png"]
+        );
+    }
+
+    #[test]
+    fn finds_no_article_in_pages_without_prose() {
+        assert!(extract_page(
+            r#"<html><body><nav><a href="/">Home</a></nav><p>Sign in to continue</p></body></html>"#
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,265 @@
+mod extract;
+
+use actix_web::rt::time::interval;
+use chrono::{DateTime, Utc};
+use common::Article;
+use sqlx::{query, query_as, FromRow, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::services::images::ImageService;
+use crate::services::link_preview::fetch::fetch_page;
+use crate::utils::sha256;
+
+use self::extract::{extract, Extracted};
+
+/// Time an extracted article is served before the page is fetched again.
+const ARTICLE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Time before a page without an article, or which failed to be fetched, is
+/// tried again.
+const FAILURE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Articles which nobody requested for this long after expiring are purged.
+const ARTICLE_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Time between purges of articles beyond their retention.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Articles of the pages stories link to, stored in the `articles` table by
+/// URL hash the same way link previews are. Pages without an article are
+/// stored as well, so these aren't fetched on every request.
+#[derive(Clone)]
+pub struct ReaderService {
+    database_pool: Arc<PgPool>,
+    images: Arc<ImageService>,
+}
+
+#[derive(Debug, FromRow)]
+struct ArticlesRow {
+    url_hash: String,
+    url: String,
+    title: Option<String>,
+    byline: Option<String>,
+    content: String,
+    text: String,
+    failed: bool,
+    expires_at: DateTime<Utc>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<ArticlesRow> for Article {
+    fn from(row: ArticlesRow) -> Self {
+        Article {
+            url: row.url,
+            title: row.title,
+            byline: row.byline,
+            content: row.content,
+            text: row.text,
+        }
+    }
+}
+
+impl ReaderService {
+    pub fn new(database_pool: Arc<PgPool>, images: Arc<ImageService>) -> Self {
+        ReaderService {
+            database_pool,
+            images,
+        }
+    }
+
+    /// Article of the page at `url`, pages without one are answered with
+    /// `Error::NotFound`. Expired articles are still served if the page
+    /// can't be fetched again.
+    pub async fn find_article(&self, url: &str) -> Result<Article> {
+        let url_hash = sha256::hash(url);
+        let cached = match self.find_stored_article(&url_hash).await {
+            Ok(cached) => cached,
+            Err(err) => {
+                eprintln!("An error ocurred reading the article:\n{:?}", err);
+                None
+            }
+        };
+
+        let stale = match cached {
+            Some(row) if row.expires_at > Utc::now() => return article_from_row(row),
+            Some(row) if !row.failed => Some(Article::from(row)),
+            _ => None,
+        };
+
+        match self.refresh(url, &url_hash).await {
+            Ok(article) => Ok(article),
+            Err(err @ Error::ForbiddenUrl(_)) | Err(err @ Error::BadRequest(_)) => Err(err),
+            Err(err) => stale.ok_or(err),
+        }
+    }
+
+    /// Purges articles beyond their retention for as long as the server
+    /// runs.
+    pub async fn run_purge(self) {
+        let mut interval = interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.purge_expired_articles().await {
+                eprintln!("An error ocurred purging articles:\n{:?}", err);
+            }
+        }
+    }
+
+    /// Fetches the page at `url` and stores its article, pages which fail
+    /// to be fetched or have no article are stored as failed. URLs which
+    /// are refused aren't stored, so these keep being answered as such.
+    async fn refresh(&self, url: &str, url_hash: &str) -> Result<Article> {
+        let extracted = match fetch_page(url).await {
+            Ok((body, page_url)) => extract(&body, &page_url, &|src| {
+                format!("{}?size=hero", self.images.proxied_url(src))
+            }),
+            Err(err @ Error::ForbiddenUrl(_)) | Err(err @ Error::BadRequest(_)) => return Err(err),
+            Err(err) => {
+                self.store_failure_or_log(url, url_hash).await;
+
+                return Err(err);
+            }
+        };
+
+        let extracted = match extracted {
+            Some(extracted) => extracted,
+            None => {
+                self.store_failure_or_log(url, url_hash).await;
+
+                return Err(Error::NotFound(String::from(
+                    "No article was found in the page",
+                )));
+            }
+        };
+
+        if let Err(err) = self.store_article(url, url_hash, &extracted).await {
+            eprintln!("An error ocurred storing the article:\n{:?}", err);
+        }
+
+        Ok(Article {
+            url: url.to_string(),
+            title: extracted.title,
+            byline: extracted.byline,
+            content: extracted.html,
+            text: extracted.text,
+        })
+    }
+
+    async fn find_stored_article(&self, url_hash: &str) -> Result<Option<ArticlesRow>> {
+        let row = query_as("SELECT * FROM articles WHERE url_hash = $1")
+            .bind(url_hash)
+            .fetch_optional(&*self.database_pool)
+            .await?;
+
+        Ok(row)
+    }
+
+    async fn store_article(&self, url: &str, url_hash: &str, extracted: &Extracted) -> Result<()> {
+        query(
+            r#"
+        INSERT INTO articles (
+            url_hash,
+            url,
+            title,
+            byline,
+            content,
+            text,
+            expires_at
+        ) VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7
+        ) ON CONFLICT (url_hash) DO UPDATE SET
+            title = EXCLUDED.title,
+            byline = EXCLUDED.byline,
+            content = EXCLUDED.content,
+            text = EXCLUDED.text,
+            expires_at = EXCLUDED.expires_at,
+            failed = FALSE,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(url_hash)
+        .bind(url)
+        .bind(extracted.title.clone())
+        .bind(extracted.byline.clone())
+        .bind(extracted.html.as_str())
+        .bind(extracted.text.as_str())
+        .bind(expires_in(ARTICLE_TTL))
+        .execute(&*self.database_pool)
+        .await?;
+
+        for image_url in extracted.images.iter() {
+            self.images.register(image_url).await?;
+        }
+
+        Ok(())
+    }
+
+    /// A previously extracted article is kept as it is, so it's still
+    /// served until the page is tried again.
+    async fn store_failure(&self, url: &str, url_hash: &str) -> Result<()> {
+        query(
+            r#"
+        INSERT INTO articles (
+            url_hash,
+            url,
+            expires_at,
+            failed
+        ) VALUES (
+            $1,
+            $2,
+            $3,
+            TRUE
+        ) ON CONFLICT (url_hash) DO UPDATE SET
+            expires_at = EXCLUDED.expires_at,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        )
+        .bind(url_hash)
+        .bind(url)
+        .bind(expires_in(FAILURE_TTL))
+        .execute(&*self.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn store_failure_or_log(&self, url: &str, url_hash: &str) {
+        if let Err(err) = self.store_failure(url, url_hash).await {
+            eprintln!("An error ocurred storing the article:\n{:?}", err);
+        }
+    }
+
+    async fn purge_expired_articles(&self) -> Result<()> {
+        query("DELETE FROM articles WHERE expires_at < $1")
+            .bind(Utc::now() - chrono::Duration::from_std(ARTICLE_RETENTION).unwrap())
+            .execute(&*self.database_pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn article_from_row(row: ArticlesRow) -> Result<Article> {
+    if row.failed {
+        return Err(Error::NotFound(String::from(
+            "No article was found in the page",
+        )));
+    }
+
+    Ok(Article::from(row))
+}
+
+fn expires_in(ttl: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(ttl).unwrap()
+}
@@ -0,0 +1,177 @@
+use common::hacker_news::{SearchHit, Type};
+use common::Page;
+use serde::Deserialize;
+use sqlx::{query_as, FromRow, PgPool};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::services::hacker_news::Mirror;
+
+const PAGE_SIZE: usize = 20;
+
+/// Maximum number of hits a client is allowed to request in a single page.
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Options given to `ts_headline` when building snippets
+const HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=2";
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSort {
+    Relevance,
+    Date,
+}
+
+impl SearchSort {
+    fn order_by(&self) -> &'static str {
+        match self {
+            SearchSort::Relevance => "ts_rank(search_vector, query) DESC, time DESC",
+            SearchSort::Date => "time DESC",
+        }
+    }
+}
+
+impl Default for SearchSort {
+    fn default() -> Self {
+        SearchSort::Relevance
+    }
+}
+
+/// Filters for a full-text search, `since` and `until` are Unix Time.
+#[derive(Debug)]
+pub struct SearchQuery {
+    pub text: String,
+    pub r#type: Option<Type>,
+    pub by: Option<String>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub sort: SearchSort,
+}
+
+/// Full-text search over the items stored in the mirror
+pub struct SearchService {
+    database_pool: Arc<PgPool>,
+}
+
+#[derive(Debug, FromRow)]
+struct SearchRow {
+    id: i64,
+    #[sqlx(rename = "type")]
+    item_type: String,
+    author: Option<String>,
+    time: i64,
+    title: Option<String>,
+    url: Option<String>,
+    score: Option<i32>,
+    parent: Option<i64>,
+    snippet: String,
+}
+
+impl SearchRow {
+    fn into_hit(self) -> Result<SearchHit> {
+        Ok(SearchHit {
+            id: self.id as u64,
+            r#type: Mirror::parse_type(&self.item_type)?,
+            by: self.author,
+            time: self.time as u64,
+            title: self.title,
+            url: self.url,
+            score: self.score.map(|score| score as u32),
+            parent: self.parent.map(|id| id as u64),
+            snippet: self.snippet,
+        })
+    }
+}
+
+impl SearchService {
+    pub fn new(database_pool: Arc<PgPool>) -> Self {
+        SearchService { database_pool }
+    }
+
+    /// Finds a page of items matching `query`, the cursor is the offset of
+    /// the page within the results.
+    pub async fn search(
+        &self,
+        query: &SearchQuery,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Page<SearchHit>> {
+        if query.text.trim().is_empty() {
+            return Err(Error::BadRequest(String::from(
+                "The \"q\" query param is required",
+            )));
+        }
+
+        let limit = limit.unwrap_or(PAGE_SIZE);
+
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(Error::BadRequest(format!(
+                "The limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+
+        let offset = match cursor {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .map_err(|_| Error::BadRequest(format!("Invalid cursor: {}", cursor)))?,
+            None => 0,
+        };
+
+        // one row past the limit is fetched to know if there are more hits
+        let rows: Vec<SearchRow> = query_as(&format!(
+            r#"
+        SELECT
+            id,
+            type,
+            author,
+            time,
+            title,
+            url,
+            score,
+            parent,
+            ts_headline(
+                'english',
+                coalesce(title, '') || ' ' || regexp_replace(coalesce(text, ''), '<[^>]*>', ' ', 'g'),
+                query,
+                $1
+            ) AS snippet
+        FROM items, websearch_to_tsquery('english', $2) query
+        WHERE search_vector @@ query
+            AND NOT deleted
+            AND NOT dead
+            AND ($3::VARCHAR IS NULL OR type = $3)
+            AND ($4::VARCHAR IS NULL OR author = $4)
+            AND ($5::BIGINT IS NULL OR time >= $5)
+            AND ($6::BIGINT IS NULL OR time <= $6)
+        ORDER BY {}
+        LIMIT $7 OFFSET $8
+        "#,
+            query.sort.order_by()
+        ))
+        .bind(HEADLINE_OPTIONS)
+        .bind(query.text.as_str())
+        .bind(query.r#type.as_ref().map(Mirror::type_name))
+        .bind(query.by.clone())
+        .bind(query.since.map(|since| since as i64))
+        .bind(query.until.map(|until| until as i64))
+        .bind((limit + 1) as i64)
+        .bind(offset as i64)
+        .fetch_all(&*self.database_pool)
+        .await?;
+
+        let has_more = rows.len() > limit;
+        let items = rows
+            .into_iter()
+            .take(limit)
+            .map(SearchRow::into_hit)
+            .collect::<Result<Vec<SearchHit>>>()?;
+
+        Ok(Page {
+            items,
+            next_cursor: Some((offset + limit).to_string()).filter(|_| has_more),
+            has_more,
+        })
+    }
+}
@@ -0,0 +1,131 @@
+//! A fake HackerNews API serving the JSON fixtures from `fixtures/`, the
+//! routes follow the layout of https://github.com/HackerNews/API so the
+//! `HackerNewsService` is exercised without network access.
+
+use actix_web::web::{get, Data, Path};
+use actix_web::{App, HttpResponse, HttpServer};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const ITEMS: &str = include_str!("fixtures/items.json");
+const LISTS: &str = include_str!("fixtures/lists.json");
+const USERS: &str = include_str!("fixtures/users.json");
+const UPDATES: &str = include_str!("fixtures/updates.json");
+
+struct Fixtures {
+    items: HashMap<u64, Value>,
+    lists: HashMap<String, Vec<u64>>,
+    users: HashMap<String, Value>,
+    updates: Value,
+    latency: Duration,
+}
+
+impl Fixtures {
+    fn load(latency: Duration) -> Self {
+        let items = serde_json::from_str::<Vec<Value>>(ITEMS)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item["id"].as_u64().unwrap(), item))
+            .collect();
+        let users = serde_json::from_str::<Vec<Value>>(USERS)
+            .unwrap()
+            .into_iter()
+            .map(|user| (user["id"].as_str().unwrap().to_string(), user))
+            .collect();
+
+        Fixtures {
+            items,
+            lists: serde_json::from_str(LISTS).unwrap(),
+            users,
+            updates: serde_json::from_str(UPDATES).unwrap(),
+            latency,
+        }
+    }
+
+    async fn delay(&self) {
+        if !self.latency.is_zero() {
+            actix_rt::time::sleep(self.latency).await;
+        }
+    }
+}
+
+pub struct FakeHackerNewsApi {
+    base_url: String,
+}
+
+impl FakeHackerNewsApi {
+    pub fn spawn() -> Self {
+        FakeHackerNewsApi::spawn_with_latency(Duration::from_millis(0))
+    }
+
+    /// Spawns the fake API delaying every response by `latency`, useful to
+    /// simulate the round trip to Firebase.
+    pub fn spawn_with_latency(latency: Duration) -> Self {
+        let fixtures = Data::new(Fixtures::load(latency));
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(fixtures.clone())
+                .route("/v0/item/{id}.json", get().to(find_item))
+                .route("/v0/user/{id}.json", get().to(find_user))
+                .route("/v0/maxitem.json", get().to(find_max_item))
+                .route("/v0/updates.json", get().to(find_updates))
+                .route("/v0/{list}.json", get().to(find_list))
+        })
+        .workers(1)
+        .disable_signals()
+        .bind("127.0.0.1:0")
+        .expect("Failed to bind the fake HackerNews API");
+        let addr = server.addrs()[0];
+
+        actix_rt::spawn(server.run());
+
+        FakeHackerNewsApi {
+            base_url: format!("http://{}/v0", addr),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+/// Firebase answers with `null` for unknown items and users
+async fn find_item(fixtures: Data<Fixtures>, id: Path<u64>) -> HttpResponse {
+    fixtures.delay().await;
+
+    match fixtures.items.get(&id.into_inner()) {
+        Some(item) => HttpResponse::Ok().json(item),
+        None => HttpResponse::Ok().json(Value::Null),
+    }
+}
+
+async fn find_user(fixtures: Data<Fixtures>, id: Path<String>) -> HttpResponse {
+    fixtures.delay().await;
+
+    match fixtures.users.get(id.as_str()) {
+        Some(user) => HttpResponse::Ok().json(user),
+        None => HttpResponse::Ok().json(Value::Null),
+    }
+}
+
+async fn find_max_item(fixtures: Data<Fixtures>) -> HttpResponse {
+    fixtures.delay().await;
+
+    HttpResponse::Ok().json(fixtures.items.keys().max())
+}
+
+async fn find_updates(fixtures: Data<Fixtures>) -> HttpResponse {
+    fixtures.delay().await;
+
+    HttpResponse::Ok().json(&fixtures.updates)
+}
+
+async fn find_list(fixtures: Data<Fixtures>, list: Path<String>) -> HttpResponse {
+    fixtures.delay().await;
+
+    match fixtures.lists.get(list.as_str()) {
+        Some(ids) => HttpResponse::Ok().json(ids),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
@@ -0,0 +1,78 @@
+use common::html::escape;
+use yew::virtual_dom::VNode;
+use yew::Html;
+
+/// Elements without content, these are never closed.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Writes out the `Html` of `common::views`, as Yew 0.18 only renders it
+/// into the DOM of a browser. Components are left out since these are only
+/// mounted by the client, so views rendered by the server link with
+/// `views::anchor`.
+pub fn render(view: &Html) -> String {
+    let mut html = String::new();
+
+    write_node(view, &mut html);
+
+    html
+}
+
+fn write_node(node: &VNode, html: &mut String) {
+    match node {
+        VNode::VTag(tag) => {
+            html.push('<');
+            html.push_str(tag.tag());
+
+            for (name, value) in tag.attributes.iter() {
+                html.push_str(&format!(r#" {}="{}""#, name, escape(value)));
+            }
+
+            html.push('>');
+
+            if VOID_ELEMENTS.contains(&tag.tag()) {
+                return;
+            }
+
+            for child in tag.children.children.iter() {
+                write_node(child, html);
+            }
+
+            html.push_str(&format!("</{}>", tag.tag()));
+        }
+        VNode::VText(text) => html.push_str(&escape(&text.text)),
+        VNode::VList(list) => {
+            for child in list.children.iter() {
+                write_node(child, html);
+            }
+        }
+        VNode::VComp(_) | VNode::VRef(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use yew::html;
+
+    use super::render;
+
+    #[test]
+    fn renders_elements_with_escaped_text_and_attributes() {
+        let view = html! {
+            <>
+                <p class="story-title">{"</p><script>alert(1)</script>"}</p>
+                <img src=r#"https://fluxcap.example/"onerror="alert(1)"# alt="" />
+            </>
+        };
+
+        assert_eq!(
+            render(&view),
+            concat!(
+                r#"<p class="story-title">&lt;/p&gt;&lt;script&gt;alert(1)&lt;/script&gt;</p>"#,
+                r#"<img alt="" src="https://fluxcap.example/&quot;onerror=&quot;alert(1)">"#,
+            )
+        );
+    }
+}